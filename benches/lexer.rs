@@ -0,0 +1,48 @@
+//! Lexing time for growing sources, run with `cargo bench`. The time per
+//! byte should stay flat as the source doubles.
+
+extern crate moeda;
+
+use moeda::token::{Kind, Tokenizer};
+use std::time::{Duration, Instant};
+
+const LINE: &str = "(defn sum-list [a b] (+ a -1 2.5e3 \"olá\\n\")) ; comment\n";
+
+fn lex(source: &str) -> usize {
+    let tokenizer = Tokenizer::new(String::from(source));
+    tokenizer
+        .take_while(|token| token.kind != Kind::EOF)
+        .count()
+}
+
+fn fastest(source: &str, runs: usize) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            lex(source);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>10} {:>10} {:>12} {:>10}",
+        "lines", "bytes", "tokens", "time", "ns/byte"
+    );
+    for exponent in 0..6 {
+        let lines = 1000 << exponent;
+        let source = LINE.repeat(lines);
+        let tokens = lex(&source);
+        let time = fastest(&source, 5);
+        println!(
+            "{:>8} {:>10} {:>10} {:>12?} {:>10.2}",
+            lines,
+            source.len(),
+            tokens,
+            time,
+            time.as_nanos() as f64 / source.len() as f64
+        );
+    }
+}
@@ -0,0 +1,721 @@
+use ast;
+use parser::{ParseError, Warning};
+use primitive::Type;
+use reader::{Datum, Form};
+use token::{Kind, Token};
+
+/// The items of a list or vector being analyzed, taken from the front.
+/// `end` is the token that closes them, where missing items are reported.
+struct Items<'a> {
+    items: &'a [Datum],
+    end: &'a Token,
+}
+
+impl<'a> Items<'a> {
+    fn new(items: &'a [Datum], end: &'a Token) -> Self {
+        Items { items, end }
+    }
+
+    fn peek(&self) -> Option<&'a Datum> {
+        self.items.first()
+    }
+
+    fn next(&mut self) -> Option<&'a Datum> {
+        let (first, rest) = self.items.split_first()?;
+        self.items = rest;
+        Some(first)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The error for a missing item, found at the closing token.
+    fn missing(&self) -> ParseError {
+        match self.end.kind {
+            Kind::EOF => ParseError::Unexpected {
+                expected: Kind::GroupEnd,
+                found: self.end.clone(),
+            },
+            _ => ParseError::Invalid {
+                found: self.end.clone(),
+                reason: String::from("unexpected token"),
+            },
+        }
+    }
+
+    /// The token where an item of `kind` was expected.
+    fn unexpected(&self, expected: Kind) -> ParseError {
+        ParseError::Unexpected {
+            expected,
+            found: self.peek().map_or(self.end, |item| &item.token).clone(),
+        }
+    }
+
+    /// The items of the `[...]` vector expected next.
+    fn vector(&mut self) -> Result<Items<'a>, ParseError> {
+        match self.peek() {
+            Some(&Datum {
+                form: Form::Vector(ref vector),
+                ref end,
+                ..
+            }) => {
+                self.next();
+                Ok(Items::new(vector, end))
+            }
+            _ => Err(self.unexpected(Kind::ArgsBegin)),
+        }
+    }
+
+    /// Checks every item was used.
+    fn finish(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(_) => Err(self.unexpected(Kind::GroupEnd)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Turns the datums read from the source into nodes to run. Errors in the
+/// expressions of a body or argument list are recorded and the analysis
+/// goes on with the next one.
+#[derive(Default)]
+pub struct Analyzer {
+    errors: Vec<ParseError>,
+    warnings: Vec<Warning>,
+    /// Names of the macros defined, calls to them are kept as data.
+    macros: Vec<String>,
+    /// Names bound by the params, `let`, `loop` and `match` patterns around
+    /// the form being analyzed, which hide macros of the same name.
+    locals: Vec<String>,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Analyzer::default()
+    }
+
+    /// Analyzes calls to `macros`, defined before this source, as macro
+    /// calls.
+    pub fn with_macros(mut self, macros: Vec<String>) -> Self {
+        self.set_macros(macros);
+        self
+    }
+
+    /// Analyzes calls to `macros` as macro calls from now on.
+    pub fn set_macros(&mut self, macros: Vec<String>) {
+        self.macros = macros;
+    }
+
+    fn is_macro(&self, name: &str) -> bool {
+        self.macros.iter().any(|macro_name| macro_name == name)
+            && !self.locals.iter().any(|local| local == name)
+    }
+
+    /// Hides the macros named like the names `pattern` binds, until the
+    /// locals are truncated back.
+    fn bind(&mut self, pattern: &ast::Node) {
+        match *pattern.operation {
+            ast::Operation::Identifier(ref name) => self.locals.push(name.clone()),
+            ast::Operation::Destructure(ref patterns) => {
+                for pattern in patterns {
+                    self.bind(pattern);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The errors recorded since the last call.
+    pub fn errors(&mut self) -> Vec<ParseError> {
+        self.errors.drain(..).collect()
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Analyzes `datum` as an expression.
+    pub fn analyze(&mut self, datum: &Datum) -> Result<ast::Node, ParseError> {
+        match datum.form {
+            Form::Literal(ref value) => match datum.token.kind {
+                Kind::Str | Kind::Integer | Kind::Float | Kind::Bolean => {
+                    ast::Node::constant(datum.token.clone())
+                }
+                _ => Ok(ast::Node::literal(value.clone()).at(datum.span)),
+            },
+            // A bare `+`, `=` or `not` is the builtin of that name.
+            Form::Symbol(_) => match datum.token.kind {
+                Kind::ID | Kind::Operator | Kind::Comparison | Kind::Logical => {
+                    Ok(ast::Node::indentifier(datum.token.clone()))
+                }
+                _ => Err(unexpected(&datum.token)),
+            },
+            Form::List(ref items) => match datum.token.kind {
+                Kind::GroupBegin => self.form(datum, items),
+                Kind::List => {
+                    let value = items[1].to_value();
+                    Ok(ast::Node::literal(value).at(datum.span))
+                }
+                Kind::Quasiquote => {
+                    let node = self.quasiquote(&items[1], &datum.token)?;
+                    Ok(node.at(datum.span))
+                }
+                _ => Err(unexpected(&datum.token)),
+            },
+            _ => Err(unexpected(&datum.token)),
+        }
+    }
+
+    /// Analyzes a `(...)` list, the kind of its first token says which
+    /// form it is.
+    fn form(&mut self, datum: &Datum, items: &[Datum]) -> Result<ast::Node, ParseError> {
+        let mut items = Items::new(items, &datum.end);
+        let head = match items.next() {
+            Some(head) => head,
+            None => return Ok(ast::Node::empty().at(datum.span)),
+        };
+
+        let node = match head.token.kind {
+            Kind::Operator => ast::Node::operator(head.token.value.clone(), self.body(items)),
+            Kind::Logical => ast::Node::logical(head.token.value.clone(), self.body(items)),
+            Kind::Comparison => ast::Node::comparison(head.token.value.clone(), self.body(items)),
+            Kind::StdOut => {
+                let node = match items.peek() {
+                    None => ast::Node::empty(),
+                    Some(_) => self.expression(&mut items)?,
+                };
+                items.finish()?;
+                ast::Node::stdout(node)
+            }
+            Kind::If => {
+                let condition = self.expression(&mut items)?;
+                let lnode = self.expression(&mut items)?;
+                if items.is_empty() {
+                    ast::Node::if_then(condition, lnode)
+                } else {
+                    let rnode = self.expression(&mut items)?;
+                    items.finish()?;
+                    ast::Node::ifelse(condition, vec![lnode, rnode])
+                }
+            }
+            Kind::Cond => self.cond(items)?,
+            Kind::Match => self.match_form(items)?,
+            Kind::Quote => {
+                let value = items.next().ok_or_else(|| items.missing())?.to_value();
+                items.finish()?;
+                ast::Node::literal(value)
+            }
+            Kind::When => {
+                let condition = self.expression(&mut items)?;
+                ast::Node::when(condition, self.body(items))
+            }
+            Kind::Do => ast::Node::block(self.body(items)),
+            Kind::VarDefine => {
+                let var = self.name(&mut items)?;
+                let node = self.expression(&mut items)?;
+                items.finish()?;
+                ast::Node::assign(var, node)
+            }
+            Kind::Let => {
+                let (bindings, body) = self.bindings(items, "let")?;
+                ast::Node::let_binding(bindings, body)
+            }
+            Kind::Loop => {
+                let (bindings, body) = self.bindings(items, "loop")?;
+                ast::Node::loop_binding(bindings, body)
+            }
+            Kind::Recur => ast::Node::recur(self.body(items)),
+            Kind::FnDefine => {
+                let name = self.name(&mut items)?;
+                let scope = self.locals.len();
+                self.bind(&name);
+                let arities = self.function(items);
+                self.locals.truncate(scope);
+                ast::Node::function_arities(name, arities?)
+            }
+            // The macro is defined when this runs, calls to it in the
+            // forms after this one are analyzed once it is.
+            Kind::MacroDefine => {
+                let name = self.name(&mut items)?;
+                ast::Node::macro_define(name, self.function(items)?)
+            }
+            // The spelled out `(quasiquote x)`, as built by `read-string`
+            // or returned by a macro.
+            Kind::ID if head.token.value == "quasiquote" => {
+                let quoted = items.next().ok_or_else(|| items.missing())?;
+                items.finish()?;
+                self.quasiquote(quoted, &head.token)?
+            }
+            Kind::ID if self.is_macro(&head.token.value) => {
+                let args = items.items.iter().map(Datum::to_value).collect();
+                ast::Node::macro_call(head.token.value.clone(), args)
+            }
+            Kind::Lambda => ast::Node::lambda_arities(self.function(items)?),
+            // The head can be a name or any form that evaluates to a
+            // function, like `((make-adder 1) 2)`.
+            Kind::ID | Kind::GroupBegin | Kind::Invalid => {
+                let callee = self.analyze(head)?;
+                ast::Node::function_call(callee, self.body(items))
+            }
+            // A single parenthesized value, like `(1)`.
+            Kind::Str | Kind::Integer | Kind::Float | Kind::List | Kind::Bolean => {
+                let node = self.analyze(head)?;
+                if !items.is_empty() {
+                    return Err(ParseError::Invalid {
+                        found: head.token.clone(),
+                        reason: String::from("unknown form head"),
+                    });
+                }
+                node
+            }
+            _ => {
+                return Err(ParseError::Invalid {
+                    found: head.token.clone(),
+                    reason: String::from("unknown form head"),
+                })
+            }
+        };
+
+        Ok(node.at(datum.span))
+    }
+
+    /// Analyzes the next item as an expression.
+    fn expression(&mut self, items: &mut Items) -> Result<ast::Node, ParseError> {
+        match items.next() {
+            Some(datum) => self.analyze(datum),
+            None => Err(items.missing()),
+        }
+    }
+
+    /// Analyzes the items left as expressions. A broken expression is
+    /// recorded and skipped so the ones after it still get checked.
+    fn body(&mut self, items: Items) -> Vec<ast::Node> {
+        let mut nodes = vec![];
+        for datum in items.items {
+            match self.analyze(datum) {
+                Ok(node) => nodes.push(node),
+                Err(error) => self.errors.push(error),
+            }
+        }
+        nodes
+    }
+
+    fn name(&mut self, items: &mut Items) -> Result<ast::Node, ParseError> {
+        match items.peek() {
+            Some(datum) if datum.token.kind == Kind::ID => {
+                items.next();
+                Ok(ast::Node::indentifier(datum.token.clone()))
+            }
+            _ => Err(items.unexpected(Kind::ID)),
+        }
+    }
+
+    /// Analyzes the datum after a `` ` ``, `found` is the token reported
+    /// when it is a lone `~@form`.
+    fn quasiquote(&mut self, datum: &Datum, found: &Token) -> Result<ast::Node, ParseError> {
+        let template = self.template(datum)?;
+        if let ast::Template::Splice(_) = template {
+            return Err(ParseError::Invalid {
+                found: found.clone(),
+                reason: String::from("nothing to splice into after"),
+            });
+        }
+        Ok(ast::Node::quasiquote(template))
+    }
+
+    /// Analyzes the items after a `` ` `` as data, except for the `~form`
+    /// and `~@form` in them, which are analyzed as code. The spelled out
+    /// `(unquote form)` and `(unquote-splicing form)` count as well.
+    fn template(&mut self, datum: &Datum) -> Result<ast::Template, ParseError> {
+        match (&datum.form, unquote_kind(datum)) {
+            (Form::List(items), Kind::Unquote) => {
+                Ok(ast::Template::Unquote(self.analyze(&items[1])?))
+            }
+            (Form::List(items), Kind::UnquoteSplice) => {
+                Ok(ast::Template::Splice(self.analyze(&items[1])?))
+            }
+            (Form::List(items), Kind::GroupBegin) => Ok(ast::Template::List(
+                items
+                    .iter()
+                    .map(|item| self.template(item))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (Form::Vector(items), _) => Ok(ast::Template::Vector(
+                items
+                    .iter()
+                    .map(|item| self.template(item))
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Ok(ast::Template::Datum(datum.to_value())),
+        }
+    }
+
+    /// Analyzes `cond test expr ...`, an `:else` test always matches.
+    fn cond(&mut self, mut items: Items) -> Result<ast::Node, ParseError> {
+        let mut clauses = vec![];
+        while let Some(datum) = items.next() {
+            let test = if datum.token.kind == Kind::ID && datum.token.value == ":else" {
+                ast::Node::literal(Type::Bool(true)).at(datum.span)
+            } else {
+                self.analyze(datum)?
+            };
+            if items.is_empty() {
+                return Err(ParseError::Invalid {
+                    found: items.end.clone(),
+                    reason: String::from("missing expression for cond clause"),
+                });
+            }
+            clauses.push((test, self.expression(&mut items)?));
+        }
+        Ok(ast::Node::cond(clauses))
+    }
+
+    /// Analyzes `match value pattern [:when guard] expr ...`, clauses after
+    /// one that matches anything are reported as unreachable.
+    fn match_form(&mut self, mut items: Items) -> Result<ast::Node, ParseError> {
+        let value = self.expression(&mut items)?;
+        let mut clauses: Vec<ast::MatchClause> = vec![];
+        while !items.is_empty() {
+            let pattern = self.match_pattern(&mut items)?;
+            let scope = self.locals.len();
+            self.bind(&pattern);
+            let clause = self.match_clause(&mut items);
+            self.locals.truncate(scope);
+            let (guard, body) = clause?;
+
+            let shadowed = clauses.iter().any(|clause| {
+                clause.is_catch_all() || (clause.guard.is_none() && clause.pattern == pattern)
+            });
+            if shadowed {
+                self.warnings.push(Warning {
+                    reason: format!("unreachable match clause `{}`", pattern.value),
+                    span: pattern.span,
+                });
+            }
+            clauses.push(ast::MatchClause {
+                pattern,
+                guard,
+                body,
+            });
+        }
+        Ok(ast::Node::match_form(value, clauses))
+    }
+
+    /// Analyzes the `[:when guard] expr` after a `match` pattern.
+    fn match_clause(
+        &mut self,
+        items: &mut Items,
+    ) -> Result<(Option<ast::Node>, ast::Node), ParseError> {
+        let guard = match items.peek() {
+            Some(datum) if datum.token.kind == Kind::ID && datum.token.value == ":when" => {
+                items.next();
+                Some(self.expression(items)?)
+            }
+            _ => None,
+        };
+        if items.is_empty() {
+            return Err(ParseError::Invalid {
+                found: items.end.clone(),
+                reason: String::from("missing expression for match clause"),
+            });
+        }
+        Ok((guard, self.expression(items)?))
+    }
+
+    /// Analyzes a `match` pattern: a literal, `nil`, `_`, a name to bind or
+    /// a `[...]` of patterns for a list.
+    fn match_pattern(&mut self, items: &mut Items) -> Result<ast::Node, ParseError> {
+        match items.peek() {
+            Some(datum) if datum.token.kind == Kind::ArgsBegin => {
+                let mut vector = items.vector()?;
+                let mut patterns = vec![];
+                while !vector.is_empty() {
+                    patterns.push(self.match_pattern(&mut vector)?);
+                }
+                check_rest(&patterns)?;
+                Ok(ast::Node::destructure(patterns).at(datum.span))
+            }
+            Some(datum)
+                if datum.token.kind != Kind::ID && matches!(datum.form, Form::Literal(_)) =>
+            {
+                items.next();
+                self.analyze(datum)
+            }
+            _ => self.name(items),
+        }
+    }
+
+    /// Analyzes the `[name value ...] body...` shared by `let` and `loop`.
+    fn bindings(
+        &mut self,
+        mut items: Items,
+        form: &str,
+    ) -> Result<(ast::Bindings, Vec<ast::Node>), ParseError> {
+        let mut vector = items.vector()?;
+        let scope = self.locals.len();
+        let bindings = self.binding_pairs(&mut vector, form);
+        let body = self.body(items);
+        self.locals.truncate(scope);
+        Ok((bindings?, body))
+    }
+
+    /// The `name value` pairs of `let` and `loop`, each value sees the
+    /// names before it.
+    fn binding_pairs(
+        &mut self,
+        vector: &mut Items,
+        form: &str,
+    ) -> Result<ast::Bindings, ParseError> {
+        let mut bindings = vec![];
+        while !vector.is_empty() {
+            let name = self.pattern(vector)?;
+            if vector.is_empty() {
+                return Err(ParseError::Invalid {
+                    found: vector.end.clone(),
+                    reason: format!("missing value for `{}` in {}", name.value, form),
+                });
+            }
+            let value = self.expression(vector)?;
+            self.bind(&name);
+            bindings.push((name, value));
+        }
+        Ok(bindings)
+    }
+
+    /// Analyzes the `[params] body...` shared by `defn`, `defmacro` and
+    /// `fn`, or one `([params] body...)` clause per arity.
+    fn function(&mut self, mut items: Items) -> Result<Vec<ast::Arity>, ParseError> {
+        match items.peek() {
+            Some(datum) if datum.token.kind == Kind::GroupBegin => {}
+            _ => return Ok(vec![self.arity(items)?]),
+        }
+
+        let mut arities: Vec<ast::Arity> = vec![];
+        while let Some(datum) = items.peek() {
+            let clause = match datum.form {
+                Form::List(ref clause) if datum.token.kind == Kind::GroupBegin => clause,
+                _ => return Err(items.unexpected(Kind::GroupBegin)),
+            };
+            items.next();
+            let arity = self.arity(Items::new(clause, &datum.end))?;
+            // A fixed clause is picked over the variadic one for the count
+            // it takes, so only clauses of the same kind can clash.
+            let clashes = |other: &ast::Arity| {
+                if arity.is_variadic() {
+                    other.is_variadic()
+                } else {
+                    !other.is_variadic() && other.required() == arity.required()
+                }
+            };
+            if arities.iter().any(clashes) {
+                let reason = if arity.is_variadic() {
+                    String::from("another clause already has a rest param")
+                } else {
+                    format!(
+                        "another clause already takes {} argument{}",
+                        arity.required(),
+                        if arity.required() == 1 { "" } else { "s" }
+                    )
+                };
+                return Err(ParseError::Invalid {
+                    found: datum.token.clone(),
+                    reason,
+                });
+            }
+            arities.push(arity);
+        }
+        Ok(arities)
+    }
+
+    fn arity(&mut self, mut items: Items) -> Result<ast::Arity, ParseError> {
+        let params = items.vector()?;
+        let params = self.params_list(params)?;
+        let scope = self.locals.len();
+        for param in &params {
+            self.bind(param);
+        }
+        let body = self.body(items);
+        self.locals.truncate(scope);
+        Ok(ast::Arity::new(params, body))
+    }
+
+    /// Analyzes the params of a `[...]`, a `&` must be followed by a single
+    /// param, which gets the arguments left.
+    fn params_list(&mut self, mut items: Items) -> Result<Vec<ast::Node>, ParseError> {
+        let mut params = vec![];
+        while !items.is_empty() {
+            params.push(self.pattern(&mut items)?);
+        }
+        check_rest(&params)?;
+        Ok(params)
+    }
+
+    /// Analyzes what a value is bound to, a name or a `[...]` of patterns
+    /// that takes a list apart, like `[head & tail]`.
+    fn pattern(&mut self, items: &mut Items) -> Result<ast::Node, ParseError> {
+        match items.peek() {
+            Some(datum) if datum.token.kind == Kind::ArgsBegin => {
+                let vector = items.vector()?;
+                let patterns = self.params_list(vector)?;
+                Ok(ast::Node::destructure(patterns).at(datum.span))
+            }
+            _ => self.name(items),
+        }
+    }
+}
+
+/// The kind of a template item, a `(unquote form)` list reads as if it
+/// was written `~form` and `(unquote-splicing form)` as `~@form`.
+fn unquote_kind(datum: &Datum) -> Kind {
+    match datum.form {
+        Form::List(ref items) if datum.token.kind == Kind::GroupBegin && items.len() == 2 => {
+            match items[0].form {
+                Form::Symbol(ref name) if name == "unquote" => Kind::Unquote,
+                Form::Symbol(ref name) if name == "unquote-splicing" => Kind::UnquoteSplice,
+                _ => Kind::GroupBegin,
+            }
+        }
+        _ => datum.token.kind.clone(),
+    }
+}
+
+fn unexpected(token: &Token) -> ParseError {
+    ParseError::Invalid {
+        found: token.clone(),
+        reason: String::from("unexpected token"),
+    }
+}
+
+/// Checks a `&` in `patterns` is followed by a single pattern.
+fn check_rest(patterns: &[ast::Node]) -> Result<(), ParseError> {
+    if let Some(index) = patterns.iter().position(|pattern| pattern.is_rest()) {
+        if patterns.len() != index + 2 || patterns[index + 1].is_rest() {
+            return Err(ParseError::Invalid {
+                found: Token::build(Kind::ID, String::from("&")).at(patterns[index].span),
+                reason: String::from("expected a single name after"),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitive::{List, Type};
+    use reader::Reader;
+    use token::Tokenizer;
+
+    fn datum(text: &str) -> Datum {
+        Reader::new(Tokenizer::new(String::from(text)))
+            .read()
+            .unwrap()
+    }
+
+    fn name(name: &str) -> ast::Node {
+        ast::Node::indentifier(Token::build(Kind::ID, String::from(name)))
+    }
+
+    #[test]
+    fn test_analyze_form() {
+        let mut analyzer = Analyzer::new();
+        assert_eq!(
+            Ok(ast::Node::if_then(
+                name("x"),
+                ast::Node::operator(String::from("inc"), vec![name("x")])
+            )),
+            analyzer.analyze(&datum("(if x (inc x))"))
+        );
+    }
+
+    #[test]
+    fn test_analyze_records_body_errors() {
+        let mut analyzer = Analyzer::new();
+        let node = analyzer.analyze(&datum("(do (if) x [1])"));
+        assert_eq!(Ok(ast::Node::block(vec![name("x")])), node);
+        let errors: Vec<String> = analyzer
+            .errors()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "Syntax error: unexpected token `)` at line 1, column 8",
+                "Syntax error: unexpected token `[` at line 1, column 12",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_analyze_macro_call() {
+        let mut analyzer = Analyzer::new();
+        assert!(analyzer
+            .analyze(&datum("(defmacro unless [c & body] c)"))
+            .is_ok());
+        match analyzer.analyze(&datum("(unless x)")) {
+            Ok(ref node) if node.value == "calling function" => {}
+            other => panic!("expected a call, got {:?}", other),
+        }
+
+        analyzer.set_macros(vec![String::from("unless")]);
+        assert_eq!(
+            Ok(ast::Node::macro_call(
+                String::from("unless"),
+                vec![
+                    Type::Symbol(String::from("x")),
+                    Type::Vector(List::new(vec![Type::Int(1)])),
+                ]
+            )),
+            analyzer.analyze(&datum("(unless x [1])"))
+        );
+    }
+
+    #[test]
+    fn test_analyze_local_hides_macro() {
+        let mut analyzer = Analyzer::new().with_macros(vec![String::from("m")]);
+        let analyzed = |analyzer: &mut Analyzer, text| {
+            let node = analyzer.analyze(&datum(text)).unwrap();
+            format!("{:?}", node).contains("MacroCall")
+        };
+
+        assert!(!analyzed(&mut analyzer, "(fn [m] (m))"));
+        assert!(!analyzed(&mut analyzer, "(let [[a m] x] (m))"));
+        assert!(!analyzed(&mut analyzer, "(match x [m] (m) _ 0)"));
+        assert!(analyzed(&mut analyzer, "(let [x (m)] x)"));
+        assert!(analyzed(&mut analyzer, "(do (fn [m] m) (m))"));
+    }
+
+    #[test]
+    fn test_analyze_spelled_out_quasiquote() {
+        let mut analyzer = Analyzer::new();
+        assert_eq!(
+            analyzer.analyze(&datum("`(a ~x ~@y)")),
+            analyzer.analyze(&datum("(quasiquote (a (unquote x) (unquote-splicing y)))"))
+        );
+    }
+
+    #[test]
+    fn test_analyze_value() {
+        let code = Type::list(vec![
+            Type::Symbol(String::from("let")),
+            Type::Vector(List::new(vec![
+                Type::Symbol(String::from("a")),
+                Type::Int(1),
+            ])),
+            Type::Symbol(String::from("a")),
+        ]);
+        let mut analyzer = Analyzer::new();
+        assert_eq!(
+            Ok(ast::Node::let_binding(
+                vec![(
+                    name("a"),
+                    ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap()
+                )],
+                vec![name("a")]
+            )),
+            analyzer.analyze(&Datum::from_value(&code, Default::default()))
+        );
+    }
+}
@@ -1,108 +1,385 @@
+use parser::ParseError;
 use primitive::Type;
-use token::Token;
+use token::{Span, Token};
+
+/// The `name value` pairs of a `let` or `loop`.
+pub type Bindings = Vec<(Node, Node)>;
+
+/// One `[params] body...` clause of a function, a function defined with
+/// several clauses runs the one that takes as many arguments as it got.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arity {
+    pub params: Vec<Node>,
+    pub body: Vec<Node>,
+}
+
+/// One `pattern [:when guard] body` clause of a `match`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchClause {
+    pub pattern: Node,
+    pub guard: Option<Node>,
+    pub body: Node,
+}
+
+impl MatchClause {
+    /// Whether the clause matches any value, so the ones after it can't be
+    /// reached.
+    pub fn is_catch_all(&self) -> bool {
+        self.guard.is_none()
+            && match *self.pattern.operation {
+                Operation::Identifier(ref name) => name != "nil",
+                _ => false,
+            }
+    }
+}
+
+/// A quasiquoted form: data with `~` and `~@` holes, filled in with the
+/// values of their code each time it runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Template {
+    Datum(Type),
+    Unquote(Node),
+    /// A list whose items go in the enclosing list.
+    Splice(Node),
+    List(Vec<Template>),
+    Vector(Vec<Template>),
+}
+
+impl Template {
+    /// The code in the holes of the template.
+    pub fn nodes(&self) -> Vec<&Node> {
+        match *self {
+            Template::Datum(_) => vec![],
+            Template::Unquote(ref node) | Template::Splice(ref node) => vec![node],
+            Template::List(ref items) | Template::Vector(ref items) => {
+                items.iter().flat_map(|item| item.nodes()).collect()
+            }
+        }
+    }
+
+    pub fn nodes_mut(&mut self) -> Vec<&mut Node> {
+        match *self {
+            Template::Datum(_) => vec![],
+            Template::Unquote(ref mut node) | Template::Splice(ref mut node) => vec![node],
+            Template::List(ref mut items) | Template::Vector(ref mut items) => {
+                items.iter_mut().flat_map(|item| item.nodes_mut()).collect()
+            }
+        }
+    }
+}
+
+/// How many of `patterns` come before a `&`.
+pub fn required(patterns: &[Node]) -> usize {
+    patterns
+        .iter()
+        .position(|pattern| pattern.is_rest())
+        .unwrap_or(patterns.len())
+}
+
+impl Arity {
+    pub fn new(params: Vec<Node>, body: Vec<Node>) -> Self {
+        Arity { params, body }
+    }
+
+    /// How many arguments the clause needs, the ones after a `&` being
+    /// optional.
+    pub fn required(&self) -> usize {
+        required(&self.params)
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        self.required() < self.params.len()
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count == self.required() || (self.is_variadic() && count > self.required())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operation {
-    Main(Node),
+    Main(Vec<Node>),
     Identifier(String),
     Operator(String, Vec<Node>),
     Comparison(String, Vec<Node>),
     Logical(String, Vec<Node>),
     Constant(Type),
+    Quasiquote(Template),
     IfElse(Node, Vec<Node>),
+    If(Node, Node),
+    Cond(Vec<(Node, Node)>),
+    Match(Node, Vec<MatchClause>),
     When(Node, Vec<Node>),
+    Do(Vec<Node>),
     Assign(Node, Node),
+    Let(Bindings, Vec<Node>),
+    Loop(Bindings, Vec<Node>),
+    Recur(Vec<Node>),
+    /// A `[...]` pattern in a binding or param list.
+    Destructure(Vec<Node>),
     StdOut(Node),
-    DefineFunction(Node, Type),
+    DefineFunction(Node, Vec<Arity>),
+    DefineMacro(Node, Vec<Arity>),
+    /// A call to a macro, with its arguments read as data.
+    MacroCall(String, Vec<Type>),
+    Lambda(Vec<Arity>),
     CallFunction(Node, Vec<Node>),
     Empty,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Node {
     pub operation: Box<Operation>,
     pub value: String,
+    pub span: Span,
+}
+
+// Like tokens, nodes are compared by what they do, not by where they were
+// written.
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        self.operation == other.operation && self.value == other.value
+    }
 }
 
 impl Node {
-    pub fn main(statements: Node) -> Self {
+    fn build(operation: Operation, value: String) -> Self {
         Node {
-            operation: Box::new(Operation::Main(statements)),
-            value: String::new(),
+            operation: Box::new(operation),
+            value,
+            span: Span::default(),
         }
     }
-    pub fn operator(token: String, nodes: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::Operator(token.clone(), nodes)),
-            value: token,
+
+    pub fn at(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Whether this is the `&` before the pattern taking the rest of a
+    /// list, a `"&"` string is matched like any other.
+    pub fn is_rest(&self) -> bool {
+        match *self.operation {
+            Operation::Identifier(ref name) => name == "&",
+            _ => false,
         }
     }
+
+    pub fn main(statements: Vec<Node>) -> Self {
+        let span = match (statements.first(), statements.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::default(),
+        };
+        Node::build(Operation::Main(statements), String::new()).at(span)
+    }
+    pub fn operator(token: String, nodes: Vec<Node>) -> Self {
+        Node::build(Operation::Operator(token.clone(), nodes), token)
+    }
     pub fn comparison(token: String, nodes: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::Comparison(token.clone(), nodes)),
-            value: token,
-        }
+        Node::build(Operation::Comparison(token.clone(), nodes), token)
     }
     pub fn logical(token: String, nodes: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::Logical(token.clone(), nodes)),
-            value: token,
-        }
+        Node::build(Operation::Logical(token.clone(), nodes), token)
     }
-    pub fn constant(token: Token) -> Self {
-        let primitive = Type::from(&token);
-        Node {
-            operation: Box::new(Operation::Constant(primitive)),
-            value: token.value,
-        }
+    pub fn constant(token: Token) -> Result<Self, ParseError> {
+        let primitive = Type::from(&token)?;
+        Ok(Node::build(Operation::Constant(primitive), token.value).at(token.span))
+    }
+    pub fn literal(value: Type) -> Self {
+        Node::build(Operation::Constant(value.clone()), value.to_string())
+    }
+    pub fn quasiquote(template: Template) -> Self {
+        Node::build(Operation::Quasiquote(template), String::from("`"))
     }
     pub fn indentifier(token: Token) -> Self {
-        Node {
-            operation: Box::new(Operation::Identifier(token.clone().value)),
-            value: token.value,
-        }
+        Node::build(Operation::Identifier(token.value.clone()), token.value).at(token.span)
     }
     pub fn stdout(node: Node) -> Self {
-        Node {
-            operation: Box::new(Operation::StdOut(node)),
-            value: String::new(),
-        }
+        Node::build(Operation::StdOut(node), String::new())
     }
     pub fn ifelse(condition: Node, nodes: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::IfElse(condition, nodes)),
-            value: String::from("if"),
-        }
+        Node::build(Operation::IfElse(condition, nodes), String::from("if"))
+    }
+    pub fn if_then(condition: Node, node: Node) -> Self {
+        Node::build(Operation::If(condition, node), String::from("if"))
+    }
+    pub fn cond(clauses: Vec<(Node, Node)>) -> Self {
+        Node::build(Operation::Cond(clauses), String::from("cond"))
+    }
+    pub fn match_form(value: Node, clauses: Vec<MatchClause>) -> Self {
+        Node::build(Operation::Match(value, clauses), String::from("match"))
     }
     pub fn when(condition: Node, body: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::When(condition, body)),
-            value: String::from("when"),
-        }
+        Node::build(Operation::When(condition, body), String::from("when"))
+    }
+    pub fn block(body: Vec<Node>) -> Self {
+        Node::build(Operation::Do(body), String::from("do"))
     }
     pub fn assign(name: Node, node: Node) -> Self {
-        Node {
-            operation: Box::new(Operation::Assign(name, node)),
-            value: String::from("def"),
-        }
+        Node::build(Operation::Assign(name, node), String::from("def"))
+    }
+    pub fn let_binding(bindings: Bindings, body: Vec<Node>) -> Self {
+        Node::build(Operation::Let(bindings, body), String::from("let"))
+    }
+    pub fn loop_binding(bindings: Bindings, body: Vec<Node>) -> Self {
+        Node::build(Operation::Loop(bindings, body), String::from("loop"))
+    }
+    pub fn recur(args: Vec<Node>) -> Self {
+        Node::build(Operation::Recur(args), String::from("recur"))
+    }
+    pub fn destructure(patterns: Vec<Node>) -> Self {
+        let value: Vec<&str> = patterns.iter().map(|node| node.value.as_ref()).collect();
+        let value = format!("[{}]", value.join(" "));
+        Node::build(Operation::Destructure(patterns), value)
     }
     pub fn function_define(name: Node, params: Vec<Node>, body: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::DefineFunction(name, Type::Func(params, body))),
-            value: String::from("defn"),
-        }
+        Node::function_arities(name, vec![Arity::new(params, body)])
     }
-    pub fn function_call(name: Node, args: Vec<Node>) -> Self {
-        Node {
-            operation: Box::new(Operation::CallFunction(name, args)),
-            value: String::from("calling function"),
-        }
+    pub fn function_arities(name: Node, arities: Vec<Arity>) -> Self {
+        Node::build(
+            Operation::DefineFunction(name, arities),
+            String::from("defn"),
+        )
+    }
+    pub fn macro_define(name: Node, arities: Vec<Arity>) -> Self {
+        Node::build(
+            Operation::DefineMacro(name, arities),
+            String::from("defmacro"),
+        )
+    }
+    pub fn macro_call(name: String, args: Vec<Type>) -> Self {
+        Node::build(Operation::MacroCall(name.clone(), args), name)
+    }
+    pub fn lambda(params: Vec<Node>, body: Vec<Node>) -> Self {
+        Node::lambda_arities(vec![Arity::new(params, body)])
+    }
+    pub fn lambda_arities(arities: Vec<Arity>) -> Self {
+        Node::build(Operation::Lambda(arities), String::from("fn"))
+    }
+    pub fn function_call(callee: Node, args: Vec<Node>) -> Self {
+        Node::build(
+            Operation::CallFunction(callee, args),
+            String::from("calling function"),
+        )
     }
 
     pub fn empty() -> Self {
-        Node {
-            operation: Box::new(Operation::Empty),
-            value: String::new(),
+        Node::build(Operation::Empty, String::new())
+    }
+
+    /// The nodes this one is made of, in source order.
+    pub fn children(&self) -> Vec<&Node> {
+        fn pairs(pairs: &[(Node, Node)]) -> Vec<&Node> {
+            pairs
+                .iter()
+                .flat_map(|pair| vec![&pair.0, &pair.1])
+                .collect()
+        }
+        fn arities(arities: &[Arity]) -> Vec<&Node> {
+            arities
+                .iter()
+                .flat_map(|arity| arity.params.iter().chain(&arity.body))
+                .collect()
+        }
+
+        match *self.operation {
+            Operation::Main(ref nodes)
+            | Operation::Operator(_, ref nodes)
+            | Operation::Comparison(_, ref nodes)
+            | Operation::Logical(_, ref nodes)
+            | Operation::Do(ref nodes)
+            | Operation::Recur(ref nodes)
+            | Operation::Destructure(ref nodes) => nodes.iter().collect(),
+            Operation::IfElse(ref first, ref nodes)
+            | Operation::When(ref first, ref nodes)
+            | Operation::CallFunction(ref first, ref nodes) => {
+                Some(first).into_iter().chain(nodes).collect()
+            }
+            Operation::If(ref first, ref second) | Operation::Assign(ref first, ref second) => {
+                vec![first, second]
+            }
+            Operation::Cond(ref clauses) => pairs(clauses),
+            Operation::Match(ref value, ref clauses) => Some(value)
+                .into_iter()
+                .chain(clauses.iter().flat_map(|clause| {
+                    Some(&clause.pattern)
+                        .into_iter()
+                        .chain(&clause.guard)
+                        .chain(Some(&clause.body))
+                }))
+                .collect(),
+            Operation::Let(ref bindings, ref body) | Operation::Loop(ref bindings, ref body) => {
+                pairs(bindings).into_iter().chain(body).collect()
+            }
+            Operation::StdOut(ref node) => vec![node],
+            Operation::DefineFunction(ref name, ref clauses)
+            | Operation::DefineMacro(ref name, ref clauses) => {
+                Some(name).into_iter().chain(arities(clauses)).collect()
+            }
+            Operation::Lambda(ref clauses) => arities(clauses),
+            Operation::Quasiquote(ref template) => template.nodes(),
+            Operation::Identifier(_)
+            | Operation::Constant(_)
+            | Operation::MacroCall(..)
+            | Operation::Empty => vec![],
+        }
+    }
+
+    /// Same as `children`, to rewrite them in place.
+    pub fn children_mut(&mut self) -> Vec<&mut Node> {
+        fn pairs(pairs: &mut [(Node, Node)]) -> Vec<&mut Node> {
+            pairs
+                .iter_mut()
+                .flat_map(|pair| vec![&mut pair.0, &mut pair.1])
+                .collect()
+        }
+        fn arities(arities: &mut [Arity]) -> Vec<&mut Node> {
+            arities
+                .iter_mut()
+                .flat_map(|arity| arity.params.iter_mut().chain(&mut arity.body))
+                .collect()
+        }
+
+        match *self.operation {
+            Operation::Main(ref mut nodes)
+            | Operation::Operator(_, ref mut nodes)
+            | Operation::Comparison(_, ref mut nodes)
+            | Operation::Logical(_, ref mut nodes)
+            | Operation::Do(ref mut nodes)
+            | Operation::Recur(ref mut nodes)
+            | Operation::Destructure(ref mut nodes) => nodes.iter_mut().collect(),
+            Operation::IfElse(ref mut first, ref mut nodes)
+            | Operation::When(ref mut first, ref mut nodes)
+            | Operation::CallFunction(ref mut first, ref mut nodes) => {
+                Some(first).into_iter().chain(nodes).collect()
+            }
+            Operation::If(ref mut first, ref mut second)
+            | Operation::Assign(ref mut first, ref mut second) => vec![first, second],
+            Operation::Cond(ref mut clauses) => pairs(clauses),
+            Operation::Match(ref mut value, ref mut clauses) => Some(value)
+                .into_iter()
+                .chain(clauses.iter_mut().flat_map(|clause| {
+                    Some(&mut clause.pattern)
+                        .into_iter()
+                        .chain(&mut clause.guard)
+                        .chain(Some(&mut clause.body))
+                }))
+                .collect(),
+            Operation::Let(ref mut bindings, ref mut body)
+            | Operation::Loop(ref mut bindings, ref mut body) => {
+                pairs(bindings).into_iter().chain(body).collect()
+            }
+            Operation::StdOut(ref mut node) => vec![node],
+            Operation::DefineFunction(ref mut name, ref mut clauses)
+            | Operation::DefineMacro(ref mut name, ref mut clauses) => {
+                Some(name).into_iter().chain(arities(clauses)).collect()
+            }
+            Operation::Lambda(ref mut clauses) => arities(clauses),
+            Operation::Quasiquote(ref mut template) => template.nodes_mut(),
+            Operation::Identifier(_)
+            | Operation::Constant(_)
+            | Operation::MacroCall(..)
+            | Operation::Empty => vec![],
         }
     }
 }
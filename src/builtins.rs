@@ -0,0 +1,241 @@
+use primitive::{List, Type};
+use reader::Reader;
+use token::Tokenizer;
+
+/// Functions that are always in scope. Names bound with `def`, `defn` or
+/// as parameters take precedence over them. The operators, comparisons and
+/// logical words are here so they can be passed around as values too.
+const NAMES: &[&str] = &[
+    "+",
+    "-",
+    "*",
+    "/",
+    "rem",
+    "inc",
+    "dec",
+    "=",
+    "/=",
+    "<",
+    ">",
+    "<=",
+    ">=",
+    "max",
+    "min",
+    "and",
+    "or",
+    "not",
+    "list",
+    "first",
+    "rest",
+    "cons",
+    "count",
+    "empty?",
+    "range",
+    "apply",
+    "macroexpand",
+    "vector",
+    "read-string",
+    "eval",
+];
+
+pub fn lookup(name: &str) -> Option<Type> {
+    NAMES
+        .iter()
+        .find(|builtin| **builtin == name)
+        .map(|builtin| Type::Builtin(builtin))
+}
+
+pub fn call(name: &str, args: Vec<Type>) -> Result<Type, String> {
+    match name {
+        "list" => Ok(Type::list(args)),
+        "vector" => Ok(Type::Vector(List::new(args))),
+        "first" => Ok(list(name, args)?.first().cloned().unwrap_or(Type::Nil)),
+        "rest" => Ok(Type::List(list(name, args)?.rest())),
+        "count" => Ok(Type::Int(list(name, args)?.len() as i64)),
+        "empty?" => Ok(Type::Bool(list(name, args)?.is_empty())),
+        "cons" => {
+            let mut args = args.into_iter();
+            match (args.next(), args.next().map(|tail| list(name, vec![tail]))) {
+                (Some(head), Some(tail)) => {
+                    let mut items = vec![head];
+                    items.extend_from_slice(tail?.items());
+                    Ok(Type::list(items))
+                }
+                _ => Err(format!("Value error: {} expects an item and a list", name)),
+            }
+        }
+        "range" => match args.as_slice() {
+            [Type::Int(end)] => Ok(Type::list((0..*end).map(Type::Int).collect())),
+            [Type::Int(start), Type::Int(end)] => {
+                Ok(Type::list((*start..*end).map(Type::Int).collect()))
+            }
+            _ => Err(format!("Value error: {} expects one or two integers", name)),
+        },
+        "read-string" => match args.as_slice() {
+            [Type::Str(source)] => read_string(source),
+            _ => Err(format!("Value error: {} expects a string", name)),
+        },
+        _ => Err(format!("Value error: {} is not callable", name)),
+    }
+}
+
+/// Reads the first form in `source` as data, `nil` if there is none.
+fn read_string(source: &str) -> Result<Type, String> {
+    let mut reader = Reader::new(Tokenizer::new(String::from(source)));
+    let datum = reader.read();
+    if !reader.errors().is_empty() {
+        return Err(format!(
+            "Value error: read-string got invalid code `{}`",
+            source
+        ));
+    }
+    Ok(datum.map_or(Type::Nil, |datum| datum.to_value()))
+}
+
+/// Splits the arguments of `apply` into the function to call and its
+/// arguments, the last argument is a list spread after the ones before it.
+pub fn spread(args: Vec<Type>) -> Result<(Type, Vec<Type>), String> {
+    let mut args = args;
+    let last = args.pop();
+    if args.is_empty() {
+        return Err(String::from(
+            "Value error: apply expects a function and a list",
+        ));
+    }
+    let function = args.remove(0);
+    let last = list("apply", last.into_iter().collect())?;
+    args.extend_from_slice(last.items());
+    Ok((function, args))
+}
+
+/// The single list argument `name` was called with.
+fn list(name: &str, args: Vec<Type>) -> Result<List, String> {
+    let mut args = args.into_iter();
+    match (args.next(), args.next()) {
+        (Some(Type::List(items)), None) | (Some(Type::Vector(items)), None) => Ok(items),
+        (Some(Type::Nil), None) => Ok(List::new(vec![])),
+        (Some(other), None) => Err(format!(
+            "Value error: {} expects a list, got {}",
+            name, other
+        )),
+        _ => Err(format!(
+            "Value error: {} expects a single list argument",
+            name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(Some(Type::Builtin("first")), lookup("first"));
+        assert_eq!(None, lookup("second"));
+        assert_eq!(Some(Type::Builtin("+")), lookup("+"));
+    }
+
+    #[test]
+    fn test_call_list() {
+        assert_eq!(
+            Ok(Type::list(vec![Type::Int(1), Type::Int(2)])),
+            call("list", vec![Type::Int(1), Type::Int(2)])
+        );
+    }
+
+    #[test]
+    fn test_call_first_and_rest() {
+        let items = Type::list(vec![Type::Int(1), Type::Int(2)]);
+        assert_eq!(Ok(Type::Int(1)), call("first", vec![items.clone()]));
+        assert_eq!(
+            Ok(Type::list(vec![Type::Int(2)])),
+            call("rest", vec![items])
+        );
+        assert_eq!(Ok(Type::Nil), call("first", vec![Type::list(vec![])]));
+        assert_eq!(
+            Ok(Type::list(vec![])),
+            call("rest", vec![Type::list(vec![])])
+        );
+    }
+
+    #[test]
+    fn test_call_first_not_a_list() {
+        assert_eq!(
+            Err(String::from("Value error: first expects a list, got 1")),
+            call("first", vec![Type::Int(1)])
+        );
+    }
+
+    #[test]
+    fn test_call_count_and_empty() {
+        let items = Type::list(vec![Type::Int(1), Type::Int(2)]);
+        assert_eq!(Ok(Type::Int(2)), call("count", vec![items.clone()]));
+        assert_eq!(Ok(Type::Bool(false)), call("empty?", vec![items]));
+        assert_eq!(Ok(Type::Bool(true)), call("empty?", vec![Type::Nil]));
+    }
+
+    #[test]
+    fn test_call_cons() {
+        assert_eq!(
+            Ok(Type::list(vec![Type::Int(0), Type::Int(1)])),
+            call("cons", vec![Type::Int(0), Type::list(vec![Type::Int(1)])])
+        );
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(
+            Ok((
+                Type::Builtin("list"),
+                vec![Type::Int(1), Type::Int(2), Type::Int(3)]
+            )),
+            spread(vec![
+                Type::Builtin("list"),
+                Type::Int(1),
+                Type::list(vec![Type::Int(2), Type::Int(3)]),
+            ])
+        );
+        assert_eq!(
+            Err(String::from("Value error: apply expects a list, got 2")),
+            spread(vec![Type::Builtin("list"), Type::Int(2)])
+        );
+    }
+
+    #[test]
+    fn test_call_read_string() {
+        assert_eq!(
+            Ok(Type::list(vec![
+                Type::Symbol(String::from("+")),
+                Type::Int(1),
+                Type::list(vec![
+                    Type::Symbol(String::from("quote")),
+                    Type::Symbol(String::from("x")),
+                ]),
+            ])),
+            call("read-string", vec![Type::Str(String::from("(+ 1 'x) 2"))])
+        );
+        assert_eq!(
+            Ok(Type::Nil),
+            call("read-string", vec![Type::Str(String::from(" ; nothing"))])
+        );
+        assert_eq!(
+            Err(String::from(
+                "Value error: read-string got invalid code `(+ 1`"
+            )),
+            call("read-string", vec![Type::Str(String::from("(+ 1"))])
+        );
+    }
+
+    #[test]
+    fn test_call_range() {
+        assert_eq!(
+            Ok(Type::list(vec![Type::Int(0), Type::Int(1)])),
+            call("range", vec![Type::Int(2)])
+        );
+        assert_eq!(
+            Ok(Type::list(vec![Type::Int(2), Type::Int(3)])),
+            call("range", vec![Type::Int(2), Type::Int(4)])
+        );
+    }
+}
@@ -1,50 +1,64 @@
-use primitive::Type;
+use primitive::{Function, Type};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
 
 pub struct FrameStack {
-    stack: Vec<Frame>,
+    stack: Vec<Env>,
 }
+
+impl Default for FrameStack {
+    fn default() -> Self {
+        FrameStack::new()
+    }
+}
+
 impl FrameStack {
     pub fn new() -> Self {
         FrameStack {
-            stack: vec![Frame::new()],
+            stack: vec![Env::new(Frame::new())],
         }
     }
     // Frame stack operations
-    pub fn current(&mut self) -> &mut Frame {
-        let stack_size = self.stack.len();
-        &mut self.stack[stack_size - 1]
+    pub fn current(&self) -> Env {
+        self.stack[self.stack.len() - 1].clone()
     }
 
-    pub fn push(&mut self, block_scope: Frame) {
+    pub fn push(&mut self, block_scope: Env) {
         self.stack.push(block_scope)
     }
 
-    pub fn pop(&mut self) -> Frame {
-        let old = self.stack.pop().unwrap();
-        for (k, v) in old.locals.iter() {
-            if self.current().has(k) {
-                self.current().locals.insert(k.clone(), v.clone());
-            }
-        }
-        old
+    pub fn pop(&mut self) -> Env {
+        self.stack.pop().unwrap()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Frame {
-    pub iparents: HashMap<String, Type>,
-    pub parents: HashMap<String, Type>,
     pub ilocals: HashMap<String, Type>,
     pub locals: HashMap<String, Type>,
+    /// The scope this one was created in, names not found here are looked
+    /// up there.
+    parent: Option<Env>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame::new()
+    }
 }
+
 impl Frame {
     pub fn new() -> Self {
         Frame {
             locals: HashMap::new(),
-            parents: HashMap::new(),
             ilocals: HashMap::new(),
-            iparents: HashMap::new(),
+            parent: None,
         }
     }
 
@@ -57,21 +71,208 @@ impl Frame {
     }
 
     pub fn get(&self, id: &str) -> Option<Type> {
-        // parenfunctionst
-        if let Some(value) = self.iparents.get(&*id) {
-            return Some(value.clone());
-        };
-        if let Some(value) = self.parents.get(&*id) {
-            return Some(value.clone());
+        if let Some(value) = self.ilocals.get(id) {
+            return Some(strong(value));
         };
-        // current
-        if let Some(value) = self.ilocals.get(&*id) {
-            return Some(value.clone());
+        if let Some(value) = self.locals.get(id) {
+            return Some(strong(value));
         };
-        if let Some(value) = self.locals.get(&*id) {
-            Some(value.clone())
-        } else {
-            None
+        match self.parent {
+            Some(ref parent) => parent.get(id),
+            None => None,
         }
     }
 }
+
+/// A frame shared between the stack and the functions defined in it, so a
+/// function sees the bindings of where it was written and not of where it
+/// is called.
+#[derive(Clone)]
+pub struct Env(Rc<RefCell<Frame>>);
+
+impl Env {
+    pub fn new(frame: Frame) -> Self {
+        Env(Rc::new(RefCell::new(frame)))
+    }
+
+    /// A new empty frame nested in this one.
+    pub fn child(&self) -> Env {
+        let mut frame = Frame::new();
+        frame.parent = Some(self.clone());
+        Env::new(frame)
+    }
+
+    pub fn has(&self, id: &str) -> bool {
+        self.0.borrow().has(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Type> {
+        self.0.borrow().get(id)
+    }
+
+    pub fn define(&self, id: String, value: Type) {
+        let value = self.held(value);
+        self.0.borrow_mut().ilocals.insert(id, value);
+    }
+
+    pub fn bind(&self, id: String, value: Type) {
+        let value = self.held(value);
+        self.0.borrow_mut().locals.insert(id, value);
+    }
+
+    /// `value` as kept in this frame. A function defined in this frame
+    /// would keep the frame alive while the frame keeps it, so it holds the
+    /// frame weakly. Anything holding the frame holds the function too.
+    fn held(&self, value: Type) -> Type {
+        if let Type::Func(ref function) = value {
+            if let Scope::Strong(ref env) = function.env {
+                if env == self {
+                    return Type::Func(Rc::new(Function {
+                        name: function.name.clone(),
+                        arities: function.arities.clone(),
+                        env: Scope::Weak(Rc::downgrade(&env.0)),
+                    }));
+                }
+            }
+        }
+        value
+    }
+}
+
+/// A value read from a frame, with a function that holds the frame weakly
+/// holding it again, as the value may outlive the frame.
+fn strong(value: &Type) -> Type {
+    if let Type::Func(ref function) = *value {
+        if let Scope::Weak(ref env) = function.env {
+            if let Some(env) = env.upgrade() {
+                return Type::Func(Rc::new(Function {
+                    name: function.name.clone(),
+                    arities: function.arities.clone(),
+                    env: Scope::Strong(Env(env)),
+                }));
+            }
+        }
+    }
+    value.clone()
+}
+
+/// The scope a function was defined in, held weakly while the function is
+/// kept in that same scope, see `Env::held`.
+#[derive(Clone)]
+pub enum Scope {
+    Strong(Env),
+    Weak(Weak<RefCell<Frame>>),
+}
+
+impl Scope {
+    pub fn env(&self) -> Option<Env> {
+        match *self {
+            Scope::Strong(ref env) => Some(env.clone()),
+            Scope::Weak(ref env) => env.upgrade().map(Env),
+        }
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Scope")
+    }
+}
+
+impl PartialEq for Scope {
+    fn eq(&self, other: &Scope) -> bool {
+        match (self.env(), other.env()) {
+            (Some(env), Some(other)) => env == other,
+            _ => false,
+        }
+    }
+}
+
+// A function is kept in the frame it captures, so printing or comparing the
+// frame itself would never end.
+impl fmt::Debug for Env {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Env")
+    }
+}
+
+impl PartialEq for Env {
+    fn eq(&self, other: &Env) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_get_from_parent() {
+        let root = Env::new(Frame::new());
+        root.define(String::from("x"), Type::Int(1));
+        let child = root.child();
+        child.bind(String::from("y"), Type::Int(2));
+
+        assert_eq!(Some(Type::Int(1)), child.get("x"));
+        assert_eq!(Some(Type::Int(2)), child.get("y"));
+        assert_eq!(None, root.get("y"));
+        assert!(!child.has("x"));
+    }
+
+    #[test]
+    fn test_env_child_shadows_parent() {
+        let root = Env::new(Frame::new());
+        root.define(String::from("x"), Type::Int(1));
+        let child = root.child();
+        child.bind(String::from("x"), Type::Int(2));
+
+        assert_eq!(Some(Type::Int(2)), child.get("x"));
+        assert_eq!(Some(Type::Int(1)), root.get("x"));
+    }
+
+    #[test]
+    fn test_env_sees_later_definitions() {
+        let root = Env::new(Frame::new());
+        let child = root.child();
+        root.define(String::from("f"), Type::Int(1));
+
+        assert_eq!(Some(Type::Int(1)), child.get("f"));
+    }
+
+    #[test]
+    fn test_env_lets_go_of_its_functions() {
+        // `(let [x 1 g (fn [y] (+ x y))] ...)`
+        let root = Env::new(Frame::new());
+        let scope = root.child();
+        scope.bind(String::from("x"), Type::Int(1));
+        let function = Function {
+            name: String::from("fn"),
+            arities: Rc::new(vec![]),
+            env: Scope::Strong(scope.clone()),
+        };
+        scope.bind(String::from("g"), Type::Func(Rc::new(function)));
+
+        let g = match scope.get("g") {
+            Some(Type::Func(g)) => g,
+            other => panic!("expected a function, got {:?}", other),
+        };
+        assert_eq!(Some(scope.clone()), g.env.env());
+
+        let frame = Rc::downgrade(&scope.0);
+        drop(scope);
+        assert!(frame.upgrade().is_some());
+        drop(g);
+        assert!(frame.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_frame_stack_push_pop() {
+        let mut stack = FrameStack::new();
+        let scope = stack.current().child();
+        stack.push(scope.clone());
+
+        assert_eq!(2, stack.depth());
+        assert_eq!(scope, stack.pop());
+        assert_eq!(1, stack.depth());
+    }
+}
@@ -1,165 +1,727 @@
-use ast::{Node, Operation};
-use frame::{Frame, FrameStack};
-use primitive::Type;
+use analyzer::Analyzer;
+use ast::{self, Arity, Bindings, MatchClause, Node, Operation, Template};
+use builtins;
+use frame::{Env, FrameStack, Scope};
+use parser;
+use primitive::{Function, List, Type};
+use reader::Datum;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+use token::{Kind, Span};
 
 pub struct Interpreter {
     pub stack: FrameStack,
+    /// Functions run on the code of their calls before it is evaluated.
+    macros: HashMap<String, Rc<Function>>,
 }
+
+/// What evaluating a form gives, a value or a jump left to the `loop` or
+/// the function call it ends.
+enum Outcome {
+    Value(Type),
+    /// Values for the next iteration of the enclosing `loop`, only given
+    /// by a `recur` in tail position.
+    Recur(Vec<Type>),
+    /// A call in tail position, left for the function being run to make
+    /// once its own frame is gone.
+    TailCall(Rc<Function>, Vec<Type>),
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
 impl Interpreter {
     pub fn new() -> Self {
         Interpreter {
             stack: FrameStack::new(),
+            macros: HashMap::new(),
         }
     }
 
+    pub fn macro_names(&self) -> Vec<String> {
+        self.macros.keys().cloned().collect()
+    }
+
+    /// Expands and evaluates the forms of `tree` one at a time, so a macro
+    /// can use what the forms before it define. Gives the value of the last
+    /// form.
+    pub fn run(&mut self, tree: Node) -> Result<Type, String> {
+        let forms = match *tree.operation {
+            Operation::Main(forms) => forms,
+            _ => vec![tree],
+        };
+        let mut result = Type::Nil;
+        for form in forms {
+            let form = self.expand(form)?;
+            result = self.eval_tree(&form)?;
+        }
+        Ok(result)
+    }
+
+    /// Replaces every call in `tree` to a defined macro with the code the
+    /// macro returns.
+    pub fn expand(&mut self, tree: Node) -> Result<Node, String> {
+        let mut tree = tree;
+        self.expand_node(&mut tree)?;
+        let forms = match *tree.operation {
+            Operation::Main(ref forms) => forms.iter().collect(),
+            _ => vec![&tree],
+        };
+        for form in forms {
+            parser::check_recur(form, false, None).map_err(|error| error.to_string())?;
+        }
+        Ok(tree)
+    }
+
+    fn expand_node(&mut self, node: &mut Node) -> Result<(), String> {
+        // A macro can return a call to another macro.
+        while let Some(expansion) = self.expand_call(node)? {
+            *node = expansion;
+        }
+        for child in node.children_mut() {
+            self.expand_node(child)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the macro `node` calls and reads back the code it returns,
+    /// placed where the call was since it has no source of its own. Gives
+    /// `None` when `node` isn't a call to a defined macro.
+    fn expand_call(&mut self, node: &Node) -> Result<Option<Node>, String> {
+        let (name, args, span) = match *node.operation {
+            Operation::MacroCall(ref name, ref args) => (name, args.clone(), node.span),
+            _ => return Ok(None),
+        };
+        let function = match self.macros.get(name) {
+            Some(function) => function.clone(),
+            None => return Ok(None),
+        };
+        function
+            .arity(args.len())
+            .map_err(|error| located(error, span))?;
+        let code = self.call_function(function, args)?;
+
+        // The returned value is analyzed as it is, with every part of it
+        // placed at the call.
+        let mut analyzer = Analyzer::new().with_macros(self.macro_names());
+        let node = analyzer.analyze(&Datum::from_value(&code, span));
+        match node {
+            Ok(node) if analyzer.errors().is_empty() => Ok(Some(node)),
+            _ => Err(located(
+                format!(
+                    "Macro error: {} returned invalid code `{}`",
+                    name,
+                    code.source()
+                ),
+                span,
+            )),
+        }
+    }
+
+    /// Expands `form` while it is a call to a macro, as data.
+    fn macroexpand(&mut self, form: Type) -> Result<Type, String> {
+        let mut form = form;
+        loop {
+            let call = match form {
+                Type::List(ref items) => match items.first() {
+                    Some(Type::Symbol(name)) => self
+                        .macros
+                        .get(name)
+                        .cloned()
+                        .map(|function| (function, items.rest().items().to_vec())),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match call {
+                Some((function, args)) => {
+                    function.arity(args.len())?;
+                    form = self.call_function(function, args)?;
+                }
+                None => return Ok(form),
+            }
+        }
+    }
+
+    /// Runs the code `form` holds in the current frame, as if it was
+    /// written at `span`.
+    fn eval_form(&mut self, form: &Type, span: Span) -> Result<Type, String> {
+        let mut analyzer = Analyzer::new().with_macros(self.macro_names());
+        let node = analyzer.analyze(&Datum::from_value(form, span));
+        let node = match (node, analyzer.errors().into_iter().next()) {
+            (Ok(node), None) => node,
+            (Err(error), _) | (_, Some(error)) => return Err(error.to_string()),
+        };
+        self.run(node)
+    }
+
     pub fn eval(&mut self, tree: Node) -> String {
-        match self.eval_tree(tree) {
+        match self.eval_tree(&tree) {
             Ok(result) => result.to_string(),
             Err(error) => error,
         }
     }
 
-    fn scope(&mut self) -> &mut Frame {
+    fn scope(&self) -> Env {
         self.stack.current()
     }
 
-    pub fn eval_tree(&mut self, tree: Node) -> Result<Type, String> {
-        let Node { operation, .. } = tree;
-        match *operation.clone() {
-            Operation::Main(statements) => self.eval_tree(statements),
-            Operation::Logical(tok, statements) => {
-                let types_vec: Vec<Type> = statements
-                    .into_iter()
-                    .map(|stm| self.eval_tree(stm).unwrap())
-                    .collect();
-                exec_logical(tok, types_vec)
-            }
-            Operation::Operator(tok, statements) => {
-                let types_vec: Vec<Type> = statements
-                    .into_iter()
-                    .map(|stm| self.eval_tree(stm).unwrap())
-                    .collect();
-                exec_operator(tok, types_vec)
-            }
-            Operation::Comparison(tok, statements) => {
-                let types_vec: Vec<Type> = statements
-                    .into_iter()
-                    .map(|stm| self.eval_tree(stm).unwrap())
-                    .collect();
-                exec_comparison(tok, types_vec)
-            }
-            Operation::When(condition, body) => {
-                let result_condition = try!(self.eval_tree(condition));
+    fn eval_all(&mut self, statements: &[Node]) -> Result<Vec<Type>, String> {
+        statements.iter().map(|stm| self.eval_tree(stm)).collect()
+    }
+
+    /// Evaluates `statements` in order and returns the value of the last one,
+    /// which is in `tail` position when the block is.
+    fn eval_block(&mut self, statements: &[Node], tail: bool) -> Result<Outcome, String> {
+        let mut result = Outcome::Value(Type::Nil);
+        for (index, stm) in statements.iter().enumerate() {
+            result = self.eval_node(stm, tail && index + 1 == statements.len())?;
+        }
+        Ok(result)
+    }
+
+    /// Binds each name in order, so later values can use earlier names,
+    /// then runs `body`. The caller pushes the frame they are bound in.
+    fn eval_let(
+        &mut self,
+        bindings: &Bindings,
+        body: &[Node],
+        tail: bool,
+    ) -> Result<Outcome, String> {
+        for (pattern, value) in bindings {
+            let value = self.eval_tree(value)?;
+            bind_pattern(&self.scope(), pattern, value)?;
+        }
+        self.eval_block(body, tail)
+    }
+
+    /// Runs the body of a `match` clause whose pattern matched, or gives
+    /// `None` when its guard fails.
+    fn eval_clause(&mut self, clause: &MatchClause, tail: bool) -> Result<Option<Outcome>, String> {
+        if let Some(ref guard) = clause.guard {
+            if !self.eval_tree(guard)?.as_bool() {
+                return Ok(None);
+            }
+        }
+        self.eval_node(&clause.body, tail).map(Some)
+    }
+
+    /// Binds the loop names like `let`, then runs `body` again each time
+    /// it ends in a `recur`, with the names bound in a new frame nested in
+    /// `parent` that takes the place of the last one.
+    fn eval_loop(
+        &mut self,
+        parent: &Env,
+        bindings: &Bindings,
+        body: &[Node],
+        tail: bool,
+    ) -> Result<Outcome, String> {
+        for (pattern, value) in bindings {
+            let value = self.eval_tree(value)?;
+            bind_pattern(&self.scope(), pattern, value)?;
+        }
+        loop {
+            match self.eval_block(body, tail)? {
+                Outcome::Recur(values) => {
+                    let iteration = parent.child();
+                    for ((pattern, _), value) in bindings.iter().zip(values) {
+                        bind_pattern(&iteration, pattern, value)?;
+                    }
+                    self.stack.pop();
+                    self.stack.push(iteration);
+                }
+                outcome => return Ok(outcome),
+            }
+        }
+    }
+
+    /// Runs the function clause for as many `args` as were passed, in a new
+    /// frame nested in the scope the function was defined in, with its
+    /// params bound to `args`. Calls in tail
+    /// position of the body come back as `Outcome::TailCall` and are run
+    /// here, after the frame of the finished call is dropped.
+    fn call_function(&mut self, function: Rc<Function>, args: Vec<Type>) -> Result<Type, String> {
+        let mut function = function;
+        let mut args = args;
+        loop {
+            let result = {
+                let arity = function.arity(args.len())?;
+                let func_frame = match function.env.env() {
+                    Some(env) => env.child(),
+                    None => {
+                        return Err(format!(
+                            "Value error: the scope {} was defined in is gone",
+                            function.name
+                        ))
+                    }
+                };
+                bind_items(&func_frame, &arity.params, args)?;
+                self.stack.push(func_frame);
+                let result = self.eval_block(&arity.body, true);
+                self.stack.pop();
+                result
+            };
+
+            match result? {
+                Outcome::TailCall(next, next_args) => {
+                    function = next;
+                    args = next_args;
+                }
+                outcome => return self.value(outcome),
+            }
+        }
+    }
+
+    /// The value `outcome` stands for, making the call it leaves.
+    fn value(&mut self, outcome: Outcome) -> Result<Type, String> {
+        match outcome {
+            Outcome::Value(value) => Ok(value),
+            Outcome::TailCall(function, args) => self.call_function(function, args),
+            // `check_recur` only lets a `recur` end the body of a `loop`.
+            Outcome::Recur(_) => Err(String::from("Syntax error: recur outside of loop")),
+        }
+    }
+
+    pub fn eval_tree(&mut self, tree: &Node) -> Result<Type, String> {
+        let outcome = self.eval_node(tree, false)?;
+        self.value(outcome)
+    }
+
+    /// Evaluates `tree`, `tail` says whether its value is the value of the
+    /// function being run. The forms that can end in a `recur` or a tail
+    /// call are run here, the others by `eval_value`.
+    fn eval_node(&mut self, tree: &Node, tail: bool) -> Result<Outcome, String> {
+        let span = tree.span;
+        match *tree.operation {
+            Operation::Main(ref statements) => self.eval_block(statements, false),
+            Operation::When(ref condition, ref body) => {
+                let result_condition = self.eval_tree(condition)?;
                 if result_condition.as_bool() {
-                    Ok(body
-                        .into_iter()
-                        .map(|stm| self.eval_tree(stm).unwrap())
-                        .last()
-                        .unwrap())
+                    self.eval_block(body, tail)
                 } else {
-                    Ok(Type::Nil)
+                    Ok(Outcome::Value(Type::Nil))
                 }
             }
-            Operation::IfElse(condition, nodes) => {
-                let result_condition = try!(self.eval_tree(condition));
+            Operation::Do(ref body) => self.eval_block(body, tail),
+            Operation::IfElse(ref condition, ref nodes) => {
+                let result_condition = self.eval_tree(condition)?;
                 if result_condition.as_bool() {
-                    self.eval_tree(nodes.into_iter().nth(0).unwrap())
+                    self.eval_node(&nodes[0], tail)
                 } else {
-                    self.eval_tree(nodes.into_iter().nth(1).unwrap())
+                    self.eval_node(&nodes[1], tail)
+                }
+            }
+            Operation::If(ref condition, ref node) => {
+                if self.eval_tree(condition)?.as_bool() {
+                    self.eval_node(node, tail)
+                } else {
+                    Ok(Outcome::Value(Type::Nil))
+                }
+            }
+            Operation::Cond(ref clauses) => {
+                for (test, node) in clauses {
+                    if self.eval_tree(test)?.as_bool() {
+                        return self.eval_node(node, tail);
+                    }
+                }
+                Ok(Outcome::Value(Type::Nil))
+            }
+            Operation::Match(ref value, ref clauses) => {
+                let value = self.eval_tree(value)?;
+                for clause in clauses {
+                    let match_frame = self.scope().child();
+                    if !match_pattern(&match_frame, &clause.pattern, &value) {
+                        continue;
+                    }
+                    self.stack.push(match_frame);
+                    let result = self.eval_clause(clause, tail);
+                    self.stack.pop();
+                    if let Some(result) = result? {
+                        return Ok(result);
+                    }
+                }
+                Err(located(
+                    format!("Value error: no match clause for {}", value),
+                    span,
+                ))
+            }
+            Operation::Let(ref bindings, ref body) => {
+                let let_frame = self.scope().child();
+                self.stack.push(let_frame);
+                let result = self.eval_let(bindings, body, tail);
+                self.stack.pop();
+                result
+            }
+            Operation::Loop(ref bindings, ref body) => {
+                let parent = self.scope();
+                self.stack.push(parent.child());
+                let result = self.eval_loop(&parent, bindings, body, tail);
+                self.stack.pop();
+                result
+            }
+            Operation::Recur(ref args) => Ok(Outcome::Recur(self.eval_all(args)?)),
+            // Only reached by trees that didn't go through `expand`.
+            Operation::MacroCall(ref name, _) => {
+                if !self.macros.contains_key(name) {
+                    return Err(located(
+                        format!("Value error: macro {} is not defined", name),
+                        span,
+                    ));
                 }
+                let tree = self.expand(tree.clone())?;
+                self.eval_node(&tree, tail)
+            }
+            Operation::CallFunction(ref callee, ref args) => {
+                let function = match *callee.operation {
+                    Operation::Identifier(ref name) => {
+                        self.scope().get(name).or_else(|| builtins::lookup(name))
+                    }
+                    _ => Some(self.eval_tree(callee)?),
+                };
+                match function {
+                    Some(function @ Type::Func(_)) | Some(function @ Type::Builtin(_)) => {
+                        let args = self.eval_all(args)?;
+                        self.apply(function, args, tail, span)
+                    }
+                    // A name is reported as written, any other head by its
+                    // value.
+                    other => {
+                        let name = match *callee.operation {
+                            Operation::Identifier(ref name) => name.clone(),
+                            _ => other.unwrap_or(Type::Nil).to_string(),
+                        };
+                        Err(located(
+                            format!("Value error: {} is not callable", name),
+                            callee.span,
+                        ))
+                    }
+                }
+            }
+            _ => self.eval_value(tree).map(Outcome::Value),
+        }
+    }
+
+    /// Evaluates the forms of `tree` that always give a value.
+    fn eval_value(&mut self, tree: &Node) -> Result<Type, String> {
+        let span = tree.span;
+        match *tree.operation {
+            Operation::Logical(ref tok, ref statements) => {
+                let types_vec = self.eval_all(statements)?;
+                exec_logical(tok.clone(), types_vec).map_err(|error| located(error, span))
+            }
+            Operation::Operator(ref tok, ref statements) => {
+                let types_vec = self.eval_all(statements)?;
+                exec_operator(tok.clone(), types_vec).map_err(|error| located(error, span))
+            }
+            Operation::Comparison(ref tok, ref statements) => {
+                let types_vec = self.eval_all(statements)?;
+                exec_comparison(tok.clone(), types_vec).map_err(|error| located(error, span))
             }
-            Operation::Assign(name, nodes) => {
-                let var_name = name.value;
-                let value = try!(self.eval_tree(nodes));
+            Operation::Assign(ref name, ref nodes) => {
+                let var_name = name.value.clone();
+                let value = self.eval_tree(nodes)?;
 
-                if self.scope().has(&*var_name) {
-                    return Err(format!(
-                        "Value error: variable {} has already defined.",
-                        var_name
+                if self.scope().has(&var_name) {
+                    return Err(located(
+                        format!("Value error: variable {} has already defined", var_name),
+                        name.span,
                     ));
                 }
 
-                self.scope().ilocals.insert(var_name, value.clone());
+                self.scope().define(var_name, value);
                 Ok(Type::Nil)
             }
-            Operation::Identifier(name) => {
-                if let Some(value) = self.scope().get(&*name) {
+            Operation::Identifier(ref name) => {
+                if let Some(value) = self.scope().get(name).or_else(|| builtins::lookup(name)) {
                     Ok(value)
                 } else {
-                    Err(format!("Variable {} doesn't exist in this context", name))
+                    Err(located(
+                        format!("Variable {} doesn't exist in this context", name),
+                        span,
+                    ))
                 }
             }
-            Operation::StdOut(stm) => {
-                let result = try!(self.eval_tree(stm));
-                print!("{}", result.to_string());
+            Operation::StdOut(ref stm) => {
+                let result = self.eval_tree(stm)?;
+                print!("{}", result);
                 Ok(Type::Nil)
             }
-            Operation::DefineFunction(name, func) => {
-                let var_name = name.value;
+            Operation::DefineFunction(ref name, ref arities) => {
+                let var_name = name.value.clone();
 
-                if self.scope().has(&*var_name) {
-                    return Err(format!(
-                        "Value error: variable {} has already defined.",
-                        var_name
+                if self.scope().has(&var_name) {
+                    return Err(located(
+                        format!("Value error: variable {} has already defined", var_name),
+                        name.span,
                     ));
                 }
-                self.scope().ilocals.insert(var_name, func);
+                let func = self.function(&name.value, arities);
+                self.scope().define(var_name, func);
                 Ok(Type::Nil)
             }
-            Operation::CallFunction(name, params) => {
-                let func_frame = self.scope().clone();
-                self.stack.push(func_frame);
+            Operation::Lambda(ref arities) => Ok(self.function("fn", arities)),
+            // Like a function, a macro closes over the scope it is defined
+            // in.
+            Operation::DefineMacro(ref name, ref arities) => {
+                let function = self.macro_function(&name.value, arities);
+                self.macros.insert(name.value.clone(), function);
+                Ok(Type::Nil)
+            }
+            Operation::Constant(ref var) => Ok(var.clone()),
+            Operation::Quasiquote(ref template) => self.eval_template(template),
+            _ => Ok(Type::Nil),
+        }
+    }
+
+    /// Calls `function` with `args`, or leaves the call to the function
+    /// being run when in `tail` position. Errors of builtins are reported at
+    /// `span`.
+    fn apply(
+        &mut self,
+        function: Type,
+        args: Vec<Type>,
+        tail: bool,
+        span: Span,
+    ) -> Result<Outcome, String> {
+        let value = match function {
+            Type::Func(function) => {
+                // Checked here too so the error points at the call, a tail
+                // call is only made after the caller returns.
+                function
+                    .arity(args.len())
+                    .map_err(|error| located(error, span))?;
+                if tail {
+                    return Ok(Outcome::TailCall(function, args));
+                }
+                self.call_function(function, args)
+            }
+            // The builtins that call back into the interpreter.
+            Type::Builtin("macroexpand") => match args.len() {
+                1 => self
+                    .macroexpand(args.into_iter().next().unwrap())
+                    .map_err(|error| located(error, span)),
+                _ => Err(located(
+                    String::from("Value error: macroexpand expects a single form"),
+                    span,
+                )),
+            },
+            Type::Builtin("eval") => match args.len() {
+                1 => self.eval_form(&args[0], span),
+                _ => Err(located(
+                    String::from("Value error: eval expects a single form"),
+                    span,
+                )),
+            },
+            Type::Builtin("apply") => {
+                let (function, args) =
+                    builtins::spread(args).map_err(|error| located(error, span))?;
+                return self.apply(function, args, tail, span);
+            }
+            Type::Builtin(builtin) => match Kind::reserved(builtin) {
+                Some(Kind::Operator) => exec_operator(builtin.to_string(), args),
+                Some(Kind::Comparison) => exec_comparison(builtin.to_string(), args),
+                Some(Kind::Logical) => exec_logical(builtin.to_string(), args),
+                _ => builtins::call(builtin, args),
+            }
+            .map_err(|error| located(error, span)),
+            other => Err(located(
+                format!("Value error: {} is not callable", other),
+                span,
+            )),
+        };
+        value.map(Outcome::Value)
+    }
 
-                let var_name = name.value;
-                if let Some(Type::Func(fparams, block)) = self.scope().get(&*var_name).clone() {
-                    for (pname, pvalue) in fparams.iter().zip(params.iter()) {
-                        let value = try!(self.eval_tree(pvalue.clone()));
-                        self.scope().parents.insert(pname.clone().value, value);
+    /// Builds the data of a quasiquoted form, with the values of the code
+    /// in its holes.
+    fn eval_template(&mut self, template: &Template) -> Result<Type, String> {
+        let items = match *template {
+            Template::Datum(ref value) => return Ok(value.clone()),
+            Template::Unquote(ref node) => return self.eval_tree(node),
+            Template::Splice(ref node) => {
+                return Err(located(
+                    String::from("Value error: nothing to splice into"),
+                    node.span,
+                ))
+            }
+            Template::List(ref items) | Template::Vector(ref items) => items,
+        };
+
+        let mut values = vec![];
+        for item in items {
+            match *item {
+                Template::Splice(ref node) => match self.eval_tree(node)? {
+                    Type::List(list) | Type::Vector(list) => values.extend_from_slice(list.items()),
+                    Type::Nil => {}
+                    other => {
+                        return Err(located(
+                            format!("Value error: ~@ expects a list, got {}", other),
+                            node.span,
+                        ))
                     }
-                    let r = block
-                        .into_iter()
-                        .map(|stm| self.eval_tree(stm).unwrap())
-                        .last()
-                        .unwrap();
-                    self.stack.pop();
-                    Ok(r)
-                } else {
-                    return Err(format!("Value error: {} is not callable", var_name));
+                },
+                _ => values.push(self.eval_template(item)?),
+            }
+        }
+        match *template {
+            Template::Vector(_) => Ok(Type::Vector(List::new(values))),
+            _ => Ok(Type::list(values)),
+        }
+    }
+
+    /// A function value that closes over the current scope.
+    fn function(&self, name: &str, arities: &[Arity]) -> Type {
+        Type::Func(self.macro_function(name, arities))
+    }
+
+    fn macro_function(&self, name: &str, arities: &[Arity]) -> Rc<Function> {
+        Rc::new(Function {
+            name: name.to_string(),
+            arities: Rc::new(arities.to_vec()),
+            env: Scope::Strong(self.scope()),
+        })
+    }
+}
+
+/// Binds `pattern` to `value` in `frame`, a `[...]` pattern takes a list
+/// apart and binds each of its patterns to the matching item.
+fn bind_pattern(frame: &Env, pattern: &Node, value: Type) -> Result<(), String> {
+    let patterns = match *pattern.operation {
+        Operation::Destructure(ref patterns) => patterns,
+        _ => {
+            frame.bind(pattern.value.clone(), value);
+            return Ok(());
+        }
+    };
+
+    let items = match value {
+        Type::List(items) => items,
+        Type::Nil => List::new(vec![]),
+        other => {
+            return Err(located(
+                format!(
+                    "Value error: {} expects a list, got {}",
+                    pattern.value, other
+                ),
+                pattern.span,
+            ))
+        }
+    };
+    let required = ast::required(patterns);
+    let variadic = required < patterns.len();
+    if items.len() < required || (!variadic && items.len() > required) {
+        return Err(located(
+            format!(
+                "Value error: {} expects {}{} item{}, got {}",
+                pattern.value,
+                if variadic { "at least " } else { "" },
+                required,
+                if required == 1 { "" } else { "s" },
+                items.len()
+            ),
+            pattern.span,
+        ));
+    }
+    bind_items(frame, patterns, items.items().to_vec())
+}
+
+/// Whether `value` has the shape of `pattern`, binding the names in the
+/// pattern to the parts of `value` in `frame` on the way.
+fn match_pattern(frame: &Env, pattern: &Node, value: &Type) -> bool {
+    match *pattern.operation {
+        Operation::Constant(ref literal) => literal == value,
+        Operation::Identifier(ref name) if name == "nil" => *value == Type::Nil,
+        Operation::Identifier(ref name) if name == "_" => true,
+        Operation::Identifier(ref name) => {
+            frame.bind(name.clone(), value.clone());
+            true
+        }
+        Operation::Destructure(ref patterns) => {
+            let items = match *value {
+                Type::List(ref items) => items,
+                _ => return false,
+            };
+            let required = ast::required(patterns);
+            if items.len() < required || (required == patterns.len() && items.len() > required) {
+                return false;
+            }
+            let heads = patterns[..required]
+                .iter()
+                .zip(items.items())
+                .all(|(pattern, item)| match_pattern(frame, pattern, item));
+            heads
+                && match patterns.get(required + 1) {
+                    Some(rest) => {
+                        let rest_items = Type::list(items.items()[required..].to_vec());
+                        match_pattern(frame, rest, &rest_items)
+                    }
+                    None => true,
                 }
+        }
+        _ => false,
+    }
+}
+
+/// Binds each pattern to its value in `frame`, the pattern after a `&`
+/// gets the values left as a list.
+fn bind_items(frame: &Env, patterns: &[Node], values: Vec<Type>) -> Result<(), String> {
+    let mut values = values.into_iter();
+    for (index, pattern) in patterns.iter().enumerate() {
+        if pattern.is_rest() {
+            if let Some(rest) = patterns.get(index + 1) {
+                bind_pattern(frame, rest, Type::list(values.collect()))?;
             }
-            Operation::Constant(var) => Ok(var),
-            _ => Ok(Type::Nil),
+            break;
+        }
+        match values.next() {
+            Some(value) => bind_pattern(frame, pattern, value)?,
+            None => break,
         }
     }
+    Ok(())
+}
+
+/// Appends where in the source the error happened.
+fn located(error: String, span: Span) -> String {
+    format!("{} at {}", error, span)
 }
 
 // TODO: "%", "incf", "decf"
 fn exec_operator(tok: String, nodes: Vec<Type>) -> Result<Type, String> {
-    let node_clone = nodes.clone();
+    let mut nodes = nodes.into_iter();
+    if tok == "+" {
+        return match nodes.next() {
+            Some(first) => nodes.try_fold(first, |acc, x| acc + x),
+            None => Ok(Type::Int(0)),
+        };
+    }
+    if (tok == "inc" || tok == "dec") && nodes.len() != 1 {
+        return Err(format!("Operation error: {} expects one argument", tok));
+    }
+
+    let first = first(&tok, &mut nodes)?;
     match tok.as_ref() {
-        "+" => Ok(nodes.into_iter().fold(Type::Int(0), |acc, x| acc + x)),
-        "-" => Ok(nodes
-            .into_iter()
-            .skip(1)
-            .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| acc - x)),
-        "*" => Ok(nodes
-            .into_iter()
-            .skip(1)
-            .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| acc * x)),
-        "/" => Ok(nodes
-            .into_iter()
-            .skip(1)
-            .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| acc / x)),
-        "rem" => Ok(nodes
-            .into_iter()
-            .skip(1)
-            .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| acc % x)),
-        "inc" => Ok(nodes.into_iter().nth(0).unwrap() + Type::Int(1)),
-        "dec" => Ok(nodes.into_iter().nth(0).unwrap() - Type::Int(1)),
+        "-" => nodes.try_fold(first, |acc, x| acc - x),
+        "*" => nodes.try_fold(first, |acc, x| acc * x),
+        "/" => nodes.try_fold(first, |acc, x| acc / x),
+        "rem" => nodes.try_fold(first, |acc, x| acc % x),
+        "inc" => first + Type::Int(1),
+        "dec" => first - Type::Int(1),
         _ => Err(format!("Operator error: {} isn't operation token", tok)),
     }
 }
 
+/// The first argument of `tok`, which needs at least one.
+fn first<I: Iterator<Item = Type>>(tok: &str, nodes: &mut I) -> Result<Type, String> {
+    nodes
+        .next()
+        .ok_or_else(|| format!("Operation error: {} expects at least one argument", tok))
+}
+
 fn exec_comparison(tok: String, nodes: Vec<Type>) -> Result<Type, String> {
     let node_clone = nodes.clone();
     match tok.as_ref() {
@@ -175,72 +737,73 @@ fn exec_comparison(tok: String, nodes: Vec<Type>) -> Result<Type, String> {
                 .take_while(|x| *x == node_clone.iter().last().unwrap());
             Ok(Type::Bool(b.count() != nodes.len()))
         }
-        ">" => {
-            let result = nodes
-                .into_iter()
-                .zip(node_clone.into_iter().skip(1))
-                .all(|b| (b.0 > b.1) == true);
-            Ok(Type::Bool(result))
-        }
-        "<" => {
-            let result = nodes
-                .into_iter()
-                .zip(node_clone.into_iter().skip(1))
-                .all(|b| (b.0 < b.1) == true);
-            Ok(Type::Bool(result))
-        }
-        ">=" => {
-            let result = nodes
-                .into_iter()
-                .zip(node_clone.into_iter().skip(1))
-                .all(|b| (b.0 >= b.1) == true);
+        ">" | "<" | ">=" | "<=" => {
+            let mut result = true;
+            for pair in nodes.windows(2) {
+                let ordering = pair[0].compare(&pair[1])?;
+                result = result && ordered(&tok, ordering);
+            }
             Ok(Type::Bool(result))
         }
-        "<=" => {
-            let result = nodes
-                .into_iter()
-                .zip(node_clone.into_iter().skip(1))
-                .all(|b| (b.0 <= b.1) == true);
-            Ok(Type::Bool(result))
+        "max" | "min" => {
+            let wanted = if tok == "max" {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            let mut nodes = nodes.into_iter();
+            let mut result = first(&tok, &mut nodes)?;
+            for node in nodes {
+                match node.compare(&result)? {
+                    Some(ordering) if ordering == wanted => result = node,
+                    Some(_) => {}
+                    // A NaN has no place among the others.
+                    None => return Ok(Type::Float(f64::NAN)),
+                }
+            }
+            Ok(result)
         }
-        "max" => Ok(nodes.into_iter().max().unwrap()),
-        "min" => Ok(nodes.into_iter().min().unwrap()),
         _ => Err(format!("Comparison error: {} isn't comparison token", tok)),
     }
 }
 
+/// Whether `ordering` satisfies the comparison `tok`, `None` never does.
+fn ordered(tok: &str, ordering: Option<Ordering>) -> bool {
+    match (tok, ordering) {
+        (_, None) => false,
+        (">", Some(ordering)) => ordering == Ordering::Greater,
+        ("<", Some(ordering)) => ordering == Ordering::Less,
+        (">=", Some(ordering)) => ordering != Ordering::Less,
+        (_, Some(ordering)) => ordering != Ordering::Greater,
+    }
+}
+
 fn exec_logical(tok: String, nodes: Vec<Type>) -> Result<Type, String> {
-    let node_clone = nodes.clone();
+    if nodes.is_empty() && tok != "not" {
+        return Ok(Type::Bool(tok == "and"));
+    }
+    if tok == "not" && nodes.len() != 1 {
+        return Err(format!("Operation error: {} expects one argument", tok));
+    }
+
+    let mut nodes = nodes.into_iter();
+    let first = first(&tok, &mut nodes)?;
     match tok.as_ref() {
-        "not" => Ok(!nodes.into_iter().nth(0).unwrap()),
-        "and" => {
-            let result =
-                nodes
-                    .into_iter()
-                    .skip(1)
-                    .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| {
-                        if !acc.as_bool() && x.as_bool() {
-                            acc
-                        } else {
-                            x
-                        }
-                    });
-            Ok(result)
-        }
-        "or" => {
-            let result =
-                nodes
-                    .into_iter()
-                    .skip(1)
-                    .fold(node_clone.into_iter().nth(0).unwrap(), |acc, x| {
-                        if acc.as_bool() || !x.as_bool() {
-                            acc
-                        } else {
-                            x
-                        }
-                    });
-            Ok(result)
-        }
+        "not" => !first,
+        "and" => Ok(nodes.fold(first, |acc, x| {
+            if !acc.as_bool() && x.as_bool() {
+                acc
+            } else {
+                x
+            }
+        })),
+        "or" => Ok(nodes.fold(first, |acc, x| {
+            if acc.as_bool() || !x.as_bool() {
+                acc
+            } else {
+                x
+            }
+        })),
         _ => Err(format!("Logicial error: {} isn't logical token", tok)),
     }
 }
@@ -293,6 +856,18 @@ mod operator {
         let values = vec![Type::Int(10)];
         assert_eq!(Ok(Type::Int(9)), exec_operator(String::from("dec"), values))
     }
+
+    #[test]
+    fn test_exec_operator_errors() {
+        assert!(exec_operator(String::from("/"), vec![Type::Int(1), Type::Int(0)]).is_err());
+        assert!(exec_operator(String::from("-"), vec![]).is_err());
+        assert!(exec_operator(String::from("inc"), vec![]).is_err());
+        assert!(exec_logical(String::from("not"), vec![]).is_err());
+        assert_eq!(
+            Ok(Type::Bool(true)),
+            exec_logical(String::from("and"), vec![])
+        );
+    }
 }
 
 #[cfg(test)]
@@ -353,6 +928,33 @@ mod comparison {
         )
     }
 
+    #[test]
+    fn test_exec_comparison_nan() {
+        let nan = || vec![Type::Float(f64::NAN), Type::Int(1)];
+        for tok in &["<", ">", "<=", ">="] {
+            assert_eq!(
+                Ok(Type::Bool(false)),
+                exec_comparison(tok.to_string(), nan())
+            );
+        }
+        match exec_comparison(
+            String::from("max"),
+            vec![Type::Float(1.0), Type::Float(f64::NAN)],
+        ) {
+            Ok(Type::Float(value)) => assert!(value.is_nan()),
+            other => panic!("expected NaN, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exec_comparison_invalid() {
+        let values = vec![Type::Int(1), Type::Str(String::from("a"))];
+        assert_eq!(
+            Err(String::from("Operation error: can't compare 1 with \"a\"")),
+            exec_comparison(String::from("<"), values)
+        )
+    }
+
     #[test]
     fn test_exec_comparison_max() {
         let values = vec![Type::Int(6), Type::Int(2), Type::Int(55)];
@@ -1,7 +1,10 @@
+pub mod analyzer;
 pub mod ast;
+pub mod builtins;
 pub mod frame;
 pub mod interpreter;
 pub mod parser;
 pub mod primitive;
+pub mod reader;
 pub mod repl;
 pub mod token;
@@ -1,22 +1,62 @@
 extern crate moeda;
 
+use std::env;
+use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::process;
 
 use moeda::repl;
 
 fn main() {
+    match env::args().nth(1) {
+        Some(path) => run_script(&path),
+        None => run_repl(),
+    }
+}
+
+fn run_script(path: &str) {
+    let source_code = match fs::read_to_string(path) {
+        Ok(source_code) => source_code,
+        Err(error) => {
+            eprintln!("moeda: can't read {}: {}", path, error);
+            process::exit(1);
+        }
+    };
+
+    let mut repl = repl::Repl::new();
+    let result = repl.run(source_code);
+    io::stdout()
+        .flush()
+        .expect("Ops... Something went wrong. :(");
+
+    for warning in repl.warnings() {
+        eprintln!("{}: {}", path, warning);
+    }
+
+    if let Err(error) = result {
+        for line in error.lines() {
+            eprintln!("{}: {}", path, line);
+        }
+        process::exit(1);
+    }
+}
+
+fn run_repl() {
     print!(">> ");
     let stdin = io::stdin();
     let mut repl = repl::Repl::new();
     while let Some(line) = stdin.lock().lines().next() {
         if let Ok(source_code) = line {
-            println!("{}", repl.eval(source_code));
+            let result = repl.eval(source_code);
+            for warning in repl.warnings() {
+                eprintln!("{}", warning);
+            }
+            println!("{}", result);
         }
         print!(">> ");
         io::stdout()
             .flush()
-            .ok()
             .expect("Ops... Something went wrong. :(");
     }
 }
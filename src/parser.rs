@@ -1,216 +1,301 @@
+use analyzer::Analyzer;
 use ast;
-use token::{Kind, Token, Tokenizer};
+use reader::{Datum, Reader};
+use std::fmt;
+use token::{Kind, Span, Token, Tokenizer};
+
+/// A syntax error found while reading the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// A token of kind `expected` was required, `found` was read instead.
+    Unexpected { expected: Kind, found: Token },
+    /// The token has the right shape but its value can't be read, like an
+    /// integer that doesn't fit in 64 bits.
+    Invalid { found: Token, reason: String },
+    /// The `(` or `[` was never closed, the file ended or the next top
+    /// level form began first.
+    Unclosed { open: Token },
+}
+
+impl ParseError {
+    pub fn found(&self) -> &Token {
+        match *self {
+            ParseError::Unexpected { ref found, .. }
+            | ParseError::Invalid { ref found, .. }
+            | ParseError::Unclosed { open: ref found } => found,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.found().span
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Unexpected {
+                ref expected,
+                ref found,
+            } if found.kind == Kind::EOF => write!(
+                f,
+                "Syntax error: expected {:?} found end of file at {}",
+                expected, found.span
+            ),
+            ParseError::Unexpected {
+                ref expected,
+                ref found,
+            } => write!(
+                f,
+                "Syntax error: expected {:?} found {:?} `{}` at {}",
+                expected, found.kind, found.value, found.span
+            ),
+            ParseError::Invalid {
+                ref found,
+                ref reason,
+            } => write!(
+                f,
+                "Syntax error: {} `{}` at {}",
+                reason, found.value, found.span
+            ),
+            ParseError::Unclosed { ref open } => write!(
+                f,
+                "Syntax error: unclosed `{}` at {}",
+                open.value, open.span
+            ),
+        }
+    }
+}
+
+/// Code that is valid but likely a mistake, like a `match` clause that
+/// can never run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub reason: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Warning: {} at {}", self.reason, self.span)
+    }
+}
 
 // Parser struct
 pub struct Parser {
-    tokenizer: Tokenizer,
+    reader: Reader,
+    analyzer: Analyzer,
 }
 
 impl Parser {
     pub fn new(lexer: Tokenizer) -> Self {
-        Parser { tokenizer: lexer }
-    }
-
-    fn statements(&mut self) -> ast::Node {
-        match self.tokenizer.advance().get() {
-            Some(Token {
-                kind: Kind::GroupBegin,
-                ..
-            }) => {
-                self.tokenizer.consume(Kind::GroupBegin);
-
-                match self.tokenizer.advance().get() {
-                    Some(Token {
-                        kind: Kind::Operator,
-                        ..
-                    }) => {
-                        let tok_operator = self.tokenizer.advance().consume(Kind::Operator);
-                        let nodes = self.args_list();
-                        self.tokenizer.consume(Kind::GroupEnd);
-                        ast::Node::operator(tok_operator.value, nodes)
-                    }
-                    Some(Token {
-                        kind: Kind::Logical,
-                        ..
-                    }) => {
-                        let tok = self.tokenizer.advance().consume(Kind::Logical);
-                        let nodes = self.args_list();
-                        self.tokenizer.consume(Kind::GroupEnd);
-                        ast::Node::logical(tok.value, nodes)
-                    }
-                    Some(Token {
-                        kind: Kind::StdOut, ..
-                    }) => {
-                        self.tokenizer.consume(Kind::StdOut);
-                        ast::Node::stdout(self.statements())
-                    }
-                    Some(Token { kind: Kind::If, .. }) => {
-                        self.tokenizer.consume(Kind::If);
-                        let condition = self.factor();
-                        let lnode = self.factor();
-                        let rnode = self.factor();
-                        ast::Node::ifelse(condition, vec![lnode, rnode])
-                    }
-                    Some(Token {
-                        kind: Kind::When, ..
-                    }) => {
-                        self.tokenizer.consume(Kind::When);
-                        let condition = self.factor();
-                        let mut body: Vec<ast::Node> = vec![];
-                        body.push(self.statements());
-                        ast::Node::when(condition, body)
-                    }
-                    Some(Token {
-                        kind: Kind::VarDefine,
-                        ..
-                    }) => {
-                        self.tokenizer.consume(Kind::VarDefine);
-                        let var = self.def();
-                        let node = self.statements();
-                        ast::Node::assign(var, node)
-                    }
-                    Some(Token {
-                        kind: Kind::FnDefine,
-                        ..
-                    }) => self.define_function(),
-                    Some(Token { kind: Kind::ID, .. }) => self.function_call(),
-                    Some(Token {
-                        kind: Kind::Comparison,
-                        ..
-                    }) => {
-                        let tok = self.tokenizer.advance().consume(Kind::Comparison);
-                        let nodes = self.args_list();
-                        self.tokenizer.consume(Kind::GroupEnd);
-                        ast::Node::comparison(tok.value, nodes)
-                    }
-                    _ => self.statements(),
-                }
-            }
-            Some(Token {
-                kind: Kind::Str, ..
-            })
-            | Some(Token {
-                kind: Kind::Integer,
-                ..
-            })
-            | Some(Token {
-                kind: Kind::List, ..
-            })
-            | Some(Token {
-                kind: Kind::Bolean, ..
-            }) => self.factor(),
-            Some(Token { kind: Kind::ID, .. }) => {
-                let id = self.def();
-                self.tokenizer.advance().consume(Kind::GroupEnd);
-                id
-            }
-
-            Some(Token {
-                kind: Kind::GroupEnd,
-                ..
-            }) => {
-                self.tokenizer.advance().consume(Kind::GroupEnd);
-                ast::Node::empty()
-            }
-            _ => ast::Node::empty(),
+        Parser {
+            reader: Reader::new(lexer),
+            analyzer: Analyzer::new(),
         }
     }
 
-    fn define_function(&mut self) -> ast::Node {
-        self.tokenizer.consume(Kind::FnDefine);
-        let name = self.def();
+    /// Reads calls to `macros`, defined before this source, as macro calls.
+    pub fn with_macros(mut self, macros: Vec<String>) -> Self {
+        self.analyzer = self.analyzer.with_macros(macros);
+        self
+    }
 
-        self.tokenizer.advance().consume(Kind::ArgsBegin);
-        let params = self.params_list();
-        self.tokenizer.consume(Kind::ArgsEnd);
-        self.tokenizer.advance();
-        let mut body: Vec<ast::Node> = vec![];
-        while self.tokenizer.current() != None {
-            let stm = self.statements();
-            if *stm.operation != ast::Operation::Empty {
-                body.push(stm);
-            }
+    /// The warnings found so far, they don't stop the code from running.
+    pub fn warnings(&self) -> &[Warning] {
+        self.analyzer.warnings()
+    }
+
+    /// Reads the next form and analyzes it, with every error found in it.
+    #[cfg(test)]
+    fn statements(&mut self) -> Result<ast::Node, Vec<ParseError>> {
+        match self.reader.read() {
+            Some(datum) => self.analyze(&datum),
+            None => Err(self.reader.errors()),
         }
-        ast::Node::function_define(name, params, body)
-    }
-
-    fn function_call(&mut self) -> ast::Node {
-        let name = self.def();
-        let args = self.args_list();
-        self.tokenizer.advance().consume(Kind::GroupEnd);
-        ast::Node::function_call(name, args)
-    }
-
-    fn factor(&mut self) -> ast::Node {
-        match self.tokenizer.advance().get() {
-            Some(Token {
-                kind: Kind::GroupBegin,
-                ..
-            })
-            | Some(Token {
-                kind: Kind::ArgsBegin,
-                ..
-            }) => self.statements(),
-            Some(Token {
-                kind: Kind::Bolean, ..
-            }) => ast::Node::constant(self.tokenizer.advance().consume(Kind::Bolean)),
-            Some(Token {
-                kind: Kind::Integer,
-                ..
-            }) => ast::Node::constant(self.tokenizer.advance().consume(Kind::Integer)),
-            Some(Token {
-                kind: Kind::Str, ..
-            }) => ast::Node::constant(self.tokenizer.advance().consume(Kind::Str)),
-            Some(Token {
-                kind: Kind::List, ..
-            }) => ast::Node::constant(self.tokenizer.advance().consume(Kind::List)),
-            Some(Token { kind: Kind::ID, .. }) => self.def(),
-            Some(Token {
-                kind: Kind::GroupEnd,
-                ..
-            }) => {
-                self.tokenizer.advance().consume(Kind::GroupEnd);
-                self.statements()
-            }
-            _ => ast::Node::empty(),
+    }
+
+    /// Reads every form of the source without analyzing them, so each can
+    /// be analyzed once the forms before it have run, with the macros they
+    /// defined.
+    pub fn read(&mut self) -> Result<Vec<Datum>, Vec<ParseError>> {
+        let mut forms = vec![];
+        while let Some(datum) = self.reader.read() {
+            forms.push(datum);
+        }
+        let errors = self.reader.errors();
+        if !errors.is_empty() {
+            return Err(first_per_spot(errors));
         }
+        Ok(forms)
     }
 
-    fn args_list(&mut self) -> Vec<ast::Node> {
-        let mut args = vec![];
+    /// Analyzes a form given by `read`, with calls to `macros` as macro
+    /// calls.
+    pub fn analyze_with(
+        &mut self,
+        datum: &Datum,
+        macros: Vec<String>,
+    ) -> Result<ast::Node, Vec<ParseError>> {
+        self.analyzer.set_macros(macros);
+        self.analyze(datum)
+    }
 
-        match self.tokenizer.advance().get() {
-            Some(Token {
-                kind: Kind::GroupEnd,
-                ..
-            }) => return args,
-            _ => args.push(self.factor()),
+    fn analyze(&mut self, datum: &Datum) -> Result<ast::Node, Vec<ParseError>> {
+        let node = self.analyzer.analyze(datum);
+        let mut errors = self.reader.errors();
+        errors.extend(self.analyzer.errors());
+        match node {
+            Ok(node) if errors.is_empty() => return Ok(node),
+            Ok(_) => {}
+            Err(error) => errors.push(error),
         }
-        args.extend(self.args_list());
-        args
+        Err(first_per_spot(errors))
     }
 
-    fn params_list(&mut self) -> Vec<ast::Node> {
-        let mut args = vec![];
+    /// Parses the whole source, collecting every syntax error found on the
+    /// way instead of stopping at the first one.
+    pub fn parse(&mut self) -> Result<ast::Node, Vec<ParseError>> {
+        let mut forms = vec![];
+        let mut errors = vec![];
+        while let Some(datum) = self.reader.read() {
+            match self.analyze(&datum) {
+                Ok(node) => match check_recur(&node, false, None) {
+                    Ok(()) => forms.push(node),
+                    Err(error) => errors.push(error),
+                },
+                Err(found) => errors.extend(found),
+            }
+        }
+        // Stray closers after the last form.
+        errors.extend(self.reader.errors());
 
-        match self.tokenizer.advance().get() {
-            Some(Token {
-                kind: Kind::ArgsEnd,
-                ..
-            }) => return args,
-            _ => args.push(self.factor()),
+        if !errors.is_empty() {
+            return Err(errors);
         }
-        args.extend(self.params_list());
-        args
+        Ok(ast::Node::main(forms))
     }
+}
 
-    fn def(&mut self) -> ast::Node {
-        let token = self.tokenizer.advance().consume(Kind::ID);
-        ast::Node::indentifier(token)
+/// `errors` in source order with one error per spot. The reader and the
+/// analyzer can both trip on the same token, like a `)` closing a list that
+/// still needs an item, or the `(` of an unclosed list missing one. The
+/// first error for a spot is enough.
+fn first_per_spot(errors: Vec<ParseError>) -> Vec<ParseError> {
+    let mut errors = errors;
+    errors.sort_by_key(|error| error.span().start.offset);
+    let mut found: Vec<ParseError> = vec![];
+    for error in errors {
+        let repeated = found.last().is_some_and(|last| {
+            last.span().start.offset == error.span().start.offset
+                || (error.found().kind == Kind::EOF
+                    && last.span().end.offset >= error.span().start.offset)
+        });
+        if !repeated {
+            found.push(error);
+        }
     }
+    found
+}
 
-    pub fn parse(&mut self) -> ast::Node {
-        ast::Node::main(self.statements())
+/// Checks every `recur` in `node` is in tail position of a `loop` and
+/// passes one value per loop binding. `tail` says whether `node` itself is
+/// in tail position and `bindings` is how many names the enclosing loop has.
+pub fn check_recur(
+    node: &ast::Node,
+    tail: bool,
+    bindings: Option<usize>,
+) -> Result<(), ParseError> {
+    use ast::Operation;
+
+    let last_in_tail = |nodes: &[ast::Node], bindings| -> Result<(), ParseError> {
+        for (index, child) in nodes.iter().enumerate() {
+            check_recur(child, tail && index + 1 == nodes.len(), bindings)?;
+        }
+        Ok(())
+    };
+
+    match *node.operation {
+        Operation::Recur(ref args) => {
+            let reason = match bindings {
+                None => Some(String::from("recur outside of loop")),
+                Some(_) if !tail => Some(String::from("recur must be in tail position")),
+                Some(count) if count != args.len() => Some(format!(
+                    "recur expects {} argument{}, got {}",
+                    count,
+                    if count == 1 { "" } else { "s" },
+                    args.len()
+                )),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                return Err(ParseError::Invalid {
+                    found: Token::build(Kind::Recur, String::from("recur")).at(node.span),
+                    reason,
+                });
+            }
+            for arg in args {
+                check_recur(arg, false, bindings)?;
+            }
+            Ok(())
+        }
+        Operation::Loop(ref loop_bindings, ref body) => {
+            for (_, value) in loop_bindings {
+                check_recur(value, false, bindings)?;
+            }
+            for (index, child) in body.iter().enumerate() {
+                check_recur(child, index + 1 == body.len(), Some(loop_bindings.len()))?;
+            }
+            Ok(())
+        }
+        Operation::If(ref condition, ref branch) => {
+            check_recur(condition, false, bindings)?;
+            check_recur(branch, tail, bindings)
+        }
+        Operation::IfElse(ref condition, ref branches) => {
+            check_recur(condition, false, bindings)?;
+            for branch in branches {
+                check_recur(branch, tail, bindings)?;
+            }
+            Ok(())
+        }
+        Operation::Cond(ref clauses) => {
+            for (test, branch) in clauses {
+                check_recur(test, false, bindings)?;
+                check_recur(branch, tail, bindings)?;
+            }
+            Ok(())
+        }
+        Operation::Match(ref value, ref clauses) => {
+            check_recur(value, false, bindings)?;
+            for clause in clauses {
+                if let Some(ref guard) = clause.guard {
+                    check_recur(guard, false, bindings)?;
+                }
+                check_recur(&clause.body, tail, bindings)?;
+            }
+            Ok(())
+        }
+        Operation::When(ref condition, ref body) => {
+            check_recur(condition, false, bindings)?;
+            last_in_tail(body, bindings)
+        }
+        Operation::Do(ref body) => last_in_tail(body, bindings),
+        Operation::Let(ref let_bindings, ref body) => {
+            for (_, value) in let_bindings {
+                check_recur(value, false, bindings)?;
+            }
+            last_in_tail(body, bindings)
+        }
+        // A function body is a new context, `recur` there has no loop.
+        Operation::DefineFunction(..) | Operation::DefineMacro(..) | Operation::Lambda(..) => node
+            .children()
+            .into_iter()
+            .try_for_each(|child| check_recur(child, false, None)),
+        _ => node
+            .children()
+            .into_iter()
+            .try_for_each(|child| check_recur(child, false, bindings)),
     }
 }
 
@@ -232,6 +317,7 @@ fn build_node_logical(tok_value: String, nodes: Vec<ast::Node>) -> ast::Node {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use primitive::{List, Type};
 
     #[test]
     fn test_expr_sum_as_node() {
@@ -239,22 +325,13 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
 
         let expected = build_node_operator(String::from("+"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -263,22 +340,13 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
 
         let expected = build_node_operator(String::from("-"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -287,22 +355,13 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
 
         let expected = build_node_operator(String::from("*"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -311,22 +370,13 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
 
         let expected = build_node_operator(String::from("/"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -335,18 +385,12 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
 
         let expected = build_node_operator(String::from("rem"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -354,13 +398,11 @@ mod tests {
         let text = "(inc 9)";
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
-        let nodes = vec![ast::Node::constant(Token {
-            kind: Kind::Integer,
-            value: String::from("9"),
-        })];
+        let nodes =
+            vec![ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap()];
 
         let expected = build_node_operator(String::from("inc"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -368,13 +410,11 @@ mod tests {
         let text = "(dec 9)";
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
-        let nodes = vec![ast::Node::constant(Token {
-            kind: Kind::Integer,
-            value: String::from("9"),
-        })];
+        let nodes =
+            vec![ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap()];
 
         let expected = build_node_operator(String::from("dec"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -383,26 +423,17 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
         let nodes_sub = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("10"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("7"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("10"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("7"))).unwrap(),
         ];
         let sub = build_node_operator(String::from("-"), nodes_sub);
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("9"))).unwrap(),
             sub,
         ];
 
         let expected = build_node_operator(String::from("+"), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -412,18 +443,12 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
 
         let expected = build_node_comparision(String::from("="), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -433,27 +458,18 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let node_mul = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("5"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("5"))).unwrap(),
         ];
         let mul = build_node_operator(String::from("*"), node_mul);
 
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
             mul,
         ];
 
         let expected = build_node_comparision(String::from("="), nodes);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -462,12 +478,10 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        let node = vec![ast::Node::constant(Token {
-            kind: Kind::Bolean,
-            value: String::from("true"),
-        })];
+        let node =
+            vec![ast::Node::constant(Token::build(Kind::Bolean, String::from("true"))).unwrap()];
         let expected = build_node_logical(String::from("not"), node);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -477,17 +491,11 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let node = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("5"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("5"))).unwrap(),
         ];
         let expected = build_node_logical(String::from("and"), node);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -497,17 +505,11 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let node = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("5"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("5"))).unwrap(),
         ];
         let expected = build_node_logical(String::from("or"), node);
-        assert_eq!(expected, parser.statements())
+        assert_eq!(expected, parser.statements().unwrap())
     }
 
     #[test]
@@ -517,18 +519,12 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let nodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
 
         let sum_node = build_node_operator(String::from("+"), nodes);
-        assert_eq!(ast::Node::stdout(sum_node), parser.statements())
+        assert_eq!(ast::Node::stdout(sum_node), parser.statements().unwrap())
     }
 
     #[test]
@@ -537,12 +533,9 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        let nodes = ast::Node::constant(Token {
-            kind: Kind::Str,
-            value: String::from("ola"),
-        });
+        let nodes = ast::Node::constant(Token::build(Kind::Str, String::from("ola"))).unwrap();
 
-        assert_eq!(ast::Node::stdout(nodes), parser.statements())
+        assert_eq!(ast::Node::stdout(nodes), parser.statements().unwrap())
     }
 
     #[test]
@@ -551,12 +544,9 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        let nodes = ast::Node::constant(Token {
-            kind: Kind::Integer,
-            value: String::from("1"),
-        });
+        let nodes = ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap();
 
-        assert_eq!(ast::Node::stdout(nodes), parser.statements())
+        assert_eq!(ast::Node::stdout(nodes), parser.statements().unwrap())
     }
 
     #[test]
@@ -565,12 +555,9 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        let nodes = ast::Node::constant(Token {
-            kind: Kind::Bolean,
-            value: String::from("true"),
-        });
+        let nodes = ast::Node::constant(Token::build(Kind::Bolean, String::from("true"))).unwrap();
 
-        assert_eq!(ast::Node::stdout(nodes), parser.statements())
+        assert_eq!(ast::Node::stdout(nodes), parser.statements().unwrap())
     }
 
     #[test]
@@ -579,7 +566,10 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        assert_eq!(ast::Node::stdout(ast::Node::empty()), parser.statements())
+        assert_eq!(
+            ast::Node::stdout(ast::Node::empty()),
+            parser.statements().unwrap()
+        )
     }
 
     #[test]
@@ -588,12 +578,90 @@ mod tests {
         let tokenizer = Tokenizer::new(String::from(text));
         let mut parser = Parser::new(tokenizer);
 
-        let nodes = ast::Node::constant(Token {
-            kind: Kind::List,
-            value: String::from("1,2,true"),
-        });
+        let nodes = ast::Node::literal(Type::list(vec![
+            Type::Int(1),
+            Type::Int(2),
+            Type::Bool(true),
+        ]));
+
+        assert_eq!(ast::Node::stdout(nodes), parser.statements().unwrap())
+    }
+
+    #[test]
+    fn test_nested_list_as_node() {
+        let text = "'(1  (\"a b\" x) (if) '())";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
 
-        assert_eq!(ast::Node::stdout(nodes), parser.statements())
+        let expected = ast::Node::literal(Type::list(vec![
+            Type::Int(1),
+            Type::list(vec![
+                Type::Str(String::from("a b")),
+                Type::Symbol(String::from("x")),
+            ]),
+            Type::list(vec![Type::Symbol(String::from("if"))]),
+            Type::list(vec![
+                Type::Symbol(String::from("quote")),
+                Type::list(vec![]),
+            ]),
+        ]));
+        assert_eq!(expected, parser.statements().unwrap())
+    }
+
+    #[test]
+    fn test_quote_form_as_node() {
+        let text = "(quote (a 1))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let expected = ast::Node::literal(Type::list(vec![
+            Type::Symbol(String::from("a")),
+            Type::Int(1),
+        ]));
+        assert_eq!(expected, parser.statements().unwrap())
+    }
+
+    #[test]
+    fn test_quasiquote_as_node() {
+        let text = "`(if ~c [~@body])";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let c = ast::Node::indentifier(Token::build(Kind::ID, String::from("c")));
+        let body = ast::Node::indentifier(Token::build(Kind::ID, String::from("body")));
+
+        let expected = ast::Node::quasiquote(ast::Template::List(vec![
+            ast::Template::Datum(Type::Symbol(String::from("if"))),
+            ast::Template::Unquote(c),
+            ast::Template::Vector(vec![ast::Template::Splice(body)]),
+        ]));
+        assert_eq!(expected, parser.statements().unwrap())
+    }
+
+    #[test]
+    fn test_splice_outside_list_error() {
+        let text = "`~@xs";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            Err(vec![ParseError::Invalid {
+                found: Token::build(Kind::Quasiquote, String::from("`")),
+                reason: String::from("nothing to splice into after"),
+            }]),
+            parser.parse()
+        )
+    }
+
+    #[test]
+    fn test_unclosed_list_error() {
+        let text = "(print '(1 (2 3))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            "Syntax error: unclosed `(` at line 1, column 1",
+            parser.parse().unwrap_err()[0].to_string()
+        );
     }
 
     #[test]
@@ -603,34 +671,16 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let condition_node = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
         let anodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
         let snodes = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
 
         let add_node = ast::Node::stdout(build_node_operator(String::from("+"), anodes));
@@ -638,10 +688,108 @@ mod tests {
         let condition_node = build_node_comparision(String::from("="), condition_node);
         assert_eq!(
             ast::Node::ifelse(condition_node, vec![add_node, sub_node]),
-            parser.statements()
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_if_without_else_as_node() {
+        let text = "(if true 1)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            ast::Node::if_then(
+                ast::Node::constant(Token::build(Kind::Bolean, String::from("true"))).unwrap(),
+                ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap()
+            ),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_cond_as_node() {
+        let text = "(cond false 1 :else 2)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            ast::Node::cond(vec![
+                (
+                    ast::Node::constant(Token::build(Kind::Bolean, String::from("false"))).unwrap(),
+                    ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+                ),
+                (
+                    ast::Node::literal(Type::Bool(true)),
+                    ast::Node::constant(Token::build(Kind::Integer, String::from("2"))).unwrap(),
+                ),
+            ]),
+            parser.statements().unwrap()
         )
     }
 
+    #[test]
+    fn test_cond_missing_expression_error() {
+        let text = "(cond true)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            Err(vec![ParseError::Invalid {
+                found: Token::build(Kind::GroupEnd, String::from(")")),
+                reason: String::from("missing expression for cond clause"),
+            }]),
+            parser.parse()
+        )
+    }
+
+    #[test]
+    fn test_match_as_node() {
+        let text = "(match x 0 \"zero\" n :when (> n 0) n)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let x = ast::Node::indentifier(Token::build(Kind::ID, String::from("x")));
+        let n = ast::Node::indentifier(Token::build(Kind::ID, String::from("n")));
+        let zero = ast::Node::constant(Token::build(Kind::Integer, String::from("0"))).unwrap();
+        let text = ast::Node::constant(Token::build(Kind::Str, String::from("zero"))).unwrap();
+
+        assert_eq!(
+            ast::Node::match_form(
+                x,
+                vec![
+                    ast::MatchClause {
+                        pattern: zero.clone(),
+                        guard: None,
+                        body: text,
+                    },
+                    ast::MatchClause {
+                        pattern: n.clone(),
+                        guard: Some(build_node_comparision(
+                            String::from(">"),
+                            vec![n.clone(), zero]
+                        )),
+                        body: n,
+                    },
+                ]
+            ),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_match_unreachable_clause_warning() {
+        let text = "(match x [a & b] a _ 0 [] 1)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert!(parser.parse().is_ok());
+        let warnings: Vec<String> = parser.warnings().iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            vec!["Warning: unreachable match clause `[]` at line 1, column 24"],
+            warnings
+        );
+    }
+
     #[test]
     fn test_when_as_node() {
         let text = "(when (= 1 1) (print \"eq\"))";
@@ -649,24 +797,53 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let condition_node = vec![
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
-            ast::Node::constant(Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
         ];
 
         let condition_node = build_node_comparision(String::from("="), condition_node);
-        let stdout = ast::Node::stdout(ast::Node::constant(Token {
-            kind: Kind::Str,
-            value: String::from("eq"),
-        }));
+        let stdout = ast::Node::stdout(
+            ast::Node::constant(Token::build(Kind::Str, String::from("eq"))).unwrap(),
+        );
         assert_eq!(
             ast::Node::when(condition_node, vec![stdout]),
-            parser.statements()
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_when_with_body_as_node() {
+        let text = "(when true (print 1) 2)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let stdout = ast::Node::stdout(
+            ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+        );
+        assert_eq!(
+            ast::Node::when(
+                ast::Node::constant(Token::build(Kind::Bolean, String::from("true"))).unwrap(),
+                vec![
+                    stdout,
+                    ast::Node::constant(Token::build(Kind::Integer, String::from("2"))).unwrap(),
+                ]
+            ),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_do_as_node() {
+        let text = "(do 1 2)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            ast::Node::block(vec![
+                ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+                ast::Node::constant(Token::build(Kind::Integer, String::from("2"))).unwrap(),
+            ]),
+            parser.statements().unwrap()
         )
     }
 
@@ -678,16 +855,10 @@ mod tests {
 
         assert_eq!(
             ast::Node::assign(
-                ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("x"),
-                }),
-                ast::Node::constant(Token {
-                    kind: Kind::Integer,
-                    value: String::from("1"),
-                }),
+                ast::Node::indentifier(Token::build(Kind::ID, String::from("x"))),
+                ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
             ),
-            parser.statements()
+            parser.statements().unwrap()
         )
     }
 
@@ -699,20 +870,17 @@ mod tests {
 
         assert_eq!(
             ast::Node::function_define(
-                ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("hello"),
-                }),
-                vec![ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("name"),
-                }),],
-                vec![ast::Node::stdout(ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("name"),
-                })),],
+                ast::Node::indentifier(Token::build(Kind::ID, String::from("hello"))),
+                vec![ast::Node::indentifier(Token::build(
+                    Kind::ID,
+                    String::from("name")
+                )),],
+                vec![ast::Node::stdout(ast::Node::indentifier(Token::build(
+                    Kind::ID,
+                    String::from("name")
+                ))),],
             ),
-            parser.statements()
+            parser.statements().unwrap()
         )
     }
 
@@ -724,32 +892,23 @@ mod tests {
 
         assert_eq!(
             ast::Node::function_define(
-                ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("hello"),
-                }),
+                ast::Node::indentifier(Token::build(Kind::ID, String::from("hello"))),
                 vec![
-                    ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("name"),
-                    }),
-                    ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("surname"),
-                    }),
+                    ast::Node::indentifier(Token::build(Kind::ID, String::from("name"))),
+                    ast::Node::indentifier(Token::build(Kind::ID, String::from("surname"))),
                 ],
                 vec![
-                    ast::Node::stdout(ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("name"),
-                    })),
-                    ast::Node::stdout(ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("surname"),
-                    })),
+                    ast::Node::stdout(ast::Node::indentifier(Token::build(
+                        Kind::ID,
+                        String::from("name")
+                    ))),
+                    ast::Node::stdout(ast::Node::indentifier(Token::build(
+                        Kind::ID,
+                        String::from("surname")
+                    ))),
                 ],
             ),
-            parser.statements()
+            parser.statements().unwrap()
         )
     }
 
@@ -760,43 +919,379 @@ mod tests {
         let mut parser = Parser::new(tokenizer);
 
         let nodes = vec![
-            ast::Node::indentifier(Token {
-                kind: Kind::ID,
-                value: String::from("b"),
-            }),
-            ast::Node::indentifier(Token {
-                kind: Kind::ID,
-                value: String::from("a"),
-            }),
+            ast::Node::indentifier(Token::build(Kind::ID, String::from("b"))),
+            ast::Node::indentifier(Token::build(Kind::ID, String::from("a"))),
         ];
 
         let eq_comparison = build_node_comparision(String::from("="), nodes);
 
         assert_eq!(
             ast::Node::function_define(
-                ast::Node::indentifier(Token {
-                    kind: Kind::ID,
-                    value: String::from("hello"),
-                }),
+                ast::Node::indentifier(Token::build(Kind::ID, String::from("hello"))),
                 vec![
-                    ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("a"),
-                    }),
-                    ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("b"),
-                    }),
+                    ast::Node::indentifier(Token::build(Kind::ID, String::from("a"))),
+                    ast::Node::indentifier(Token::build(Kind::ID, String::from("b"))),
                 ],
                 vec![
                     ast::Node::stdout(eq_comparison),
-                    ast::Node::stdout(ast::Node::indentifier(Token {
-                        kind: Kind::ID,
-                        value: String::from("b"),
-                    })),
+                    ast::Node::stdout(ast::Node::indentifier(Token::build(
+                        Kind::ID,
+                        String::from("b")
+                    ))),
                 ],
             ),
-            parser.statements()
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_let_as_node() {
+        let text = "(let [a 1 b a] b)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let a = ast::Node::indentifier(Token::build(Kind::ID, String::from("a")));
+        let b = ast::Node::indentifier(Token::build(Kind::ID, String::from("b")));
+        let one = ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap();
+
+        assert_eq!(
+            ast::Node::let_binding(vec![(a.clone(), one), (b.clone(), a)], vec![b]),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_let_missing_value_error() {
+        let text = "(let [a 1 b] b)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            Err(vec![ParseError::Invalid {
+                found: Token::build(Kind::ArgsEnd, String::from("]")),
+                reason: String::from("missing value for `b` in let"),
+            }]),
+            parser.parse()
+        )
+    }
+
+    #[test]
+    fn test_loop_as_node() {
+        let text = "(loop [i 0] (if (< i 3) (recur (inc i)) i))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let i = ast::Node::indentifier(Token::build(Kind::ID, String::from("i")));
+        let zero = ast::Node::constant(Token::build(Kind::Integer, String::from("0"))).unwrap();
+        let three = ast::Node::constant(Token::build(Kind::Integer, String::from("3"))).unwrap();
+
+        let condition = build_node_comparision(String::from("<"), vec![i.clone(), three]);
+        let recur = ast::Node::recur(vec![build_node_operator(
+            String::from("inc"),
+            vec![i.clone()],
+        )]);
+        assert_eq!(
+            ast::Node::main(vec![ast::Node::loop_binding(
+                vec![(i.clone(), zero)],
+                vec![ast::Node::ifelse(condition, vec![recur, i])]
+            )]),
+            parser.parse().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_recur_not_in_tail_position_error() {
+        let text = "(loop [i 0]\n  (+ 1 (recur (inc i))))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let errors = parser.parse().unwrap_err();
+        assert_eq!(
+            String::from(
+                "Syntax error: recur must be in tail position `recur` at line 2, column 8"
+            ),
+            errors[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_recur_outside_loop_error() {
+        let text = "(loop [i 0] (fn [] (recur 1))) (recur)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let errors = parser.parse().unwrap_err();
+        assert_eq!(2, errors.len());
+        assert_eq!(
+            ParseError::Invalid {
+                found: Token::build(Kind::Recur, String::from("recur")),
+                reason: String::from("recur outside of loop"),
+            },
+            errors[1]
+        );
+    }
+
+    #[test]
+    fn test_recur_arguments_error() {
+        let text = "(loop [a 0 b 1] (recur 1))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            Err(vec![ParseError::Invalid {
+                found: Token::build(Kind::Recur, String::from("recur")),
+                reason: String::from("recur expects 2 arguments, got 1"),
+            }]),
+            parser.parse()
+        );
+    }
+
+    #[test]
+    fn test_lambda_as_node() {
+        let text = "(fn [x] (* x x))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let x = ast::Node::indentifier(Token::build(Kind::ID, String::from("x")));
+
+        assert_eq!(
+            ast::Node::lambda(
+                vec![x.clone()],
+                vec![build_node_operator(String::from("*"), vec![x.clone(), x])]
+            ),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_variadic_lambda_as_node() {
+        let text = "(fn [x & more] more)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let x = ast::Node::indentifier(Token::build(Kind::ID, String::from("x")));
+        let rest = ast::Node::indentifier(Token::build(Kind::ID, String::from("&")));
+        let more = ast::Node::indentifier(Token::build(Kind::ID, String::from("more")));
+
+        assert_eq!(
+            ast::Node::lambda(vec![x, rest, more.clone()], vec![more]),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_variadic_params_error() {
+        let text = "(defn f [x & a b] x)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            Err(vec![ParseError::Invalid {
+                found: Token::build(Kind::ID, String::from("&")),
+                reason: String::from("expected a single name after"),
+            }]),
+            parser.parse()
+        )
+    }
+
+    #[test]
+    fn test_multi_arity_define_function() {
+        let text = "(defn f ([] 0) ([x] x))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let f = ast::Node::indentifier(Token::build(Kind::ID, String::from("f")));
+        let x = ast::Node::indentifier(Token::build(Kind::ID, String::from("x")));
+        let zero = ast::Node::constant(Token::build(Kind::Integer, String::from("0"))).unwrap();
+
+        assert_eq!(
+            ast::Node::function_arities(
+                f,
+                vec![
+                    ast::Arity::new(vec![], vec![zero]),
+                    ast::Arity::new(vec![x.clone()], vec![x]),
+                ]
+            ),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_destructure_as_node() {
+        let text = "(fn [[head & tail]] head)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let head = ast::Node::indentifier(Token::build(Kind::ID, String::from("head")));
+        let rest = ast::Node::indentifier(Token::build(Kind::ID, String::from("&")));
+        let tail = ast::Node::indentifier(Token::build(Kind::ID, String::from("tail")));
+        let pattern = ast::Node::destructure(vec![head.clone(), rest, tail]);
+
+        assert_eq!("[head & tail]", pattern.value);
+        assert_eq!(
+            ast::Node::lambda(vec![pattern], vec![head]),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_macro_call_reads_data() {
+        let text = "(unless (f 1) [x] y)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer).with_macros(vec![String::from("unless")]);
+
+        let call = parser.statements().unwrap();
+        assert_eq!(
+            ast::Node::macro_call(
+                String::from("unless"),
+                vec![
+                    Type::list(vec![Type::Symbol(String::from("f")), Type::Int(1)]),
+                    Type::Vector(List::new(vec![Type::Symbol(String::from("x"))])),
+                    Type::Symbol(String::from("y")),
+                ]
+            ),
+            call
+        )
+    }
+
+    #[test]
+    fn test_call_with_macros_from_before() {
+        let text = "(twice x)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer).with_macros(vec![String::from("twice")]);
+
+        assert_eq!(
+            ast::Node::macro_call(String::from("twice"), vec![Type::Symbol(String::from("x"))]),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_call_expression_head() {
+        let text = "((f 1) 2)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+        let f = ast::Node::indentifier(Token::build(Kind::ID, String::from("f")));
+        let one = ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap();
+        let two = ast::Node::constant(Token::build(Kind::Integer, String::from("2"))).unwrap();
+
+        assert_eq!(
+            ast::Node::function_call(ast::Node::function_call(f, vec![one]), vec![two]),
+            parser.statements().unwrap()
+        )
+    }
+
+    #[test]
+    fn test_parse_unexpected_token_error() {
+        let text = "(defn hello (name) (print name))";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let error = parser.parse().unwrap_err().remove(0);
+        assert_eq!(
+            ParseError::Unexpected {
+                expected: Kind::ArgsBegin,
+                found: Token::build(Kind::ID, String::from("name")),
+            },
+            error
+        );
+        assert_eq!(14, error.span().start.column);
+    }
+
+    #[test]
+    fn test_parse_end_of_file_error() {
+        let text = "(+ 1 2";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            "Syntax error: unclosed `(` at line 1, column 1",
+            parser.parse().unwrap_err()[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_integer_error() {
+        let text = "(+ 1 99999999999999999999)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        assert_eq!(
+            ParseError::Invalid {
+                found: Token::build(Kind::Integer, String::from("99999999999999999999")),
+                reason: String::from("invalid integer"),
+            },
+            parser.parse().unwrap_err()[0]
+        );
+    }
+
+    #[test]
+    fn test_parse_collects_every_error() {
+        let text = "(defn f (x) (* x 2))\n(1 2)\n(+ 1 ] 2)\n(print (+ 1 2)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let errors: Vec<String> = parser
+            .parse()
+            .unwrap_err()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "Syntax error: expected ArgsBegin found ID `x` at line 1, column 10",
+                "Syntax error: unknown form head `1` at line 2, column 2",
+                "Syntax error: unexpected token `]` at line 3, column 6",
+                "Syntax error: unclosed `(` at line 4, column 1",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_parse_recovers_inside_form() {
+        let text = "(+ (cond true) (foo] 2) 3)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let errors = parser.parse().unwrap_err();
+        assert_eq!(2, errors.len());
+        assert_eq!(14, errors[0].span().start.column);
+        assert_eq!(20, errors[1].span().start.column);
+    }
+
+    #[test]
+    fn test_parse_reports_each_spot_once() {
+        let text = "(let [a) a)\n(if true";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let errors: Vec<String> = parser
+            .parse()
+            .unwrap_err()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "Syntax error: expected ArgsEnd found GroupEnd `)` at line 1, column 8",
+                "Syntax error: unexpected token `)` at line 1, column 11",
+                "Syntax error: unclosed `(` at line 2, column 1",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_forms() {
+        let text = "(def x 1) (print x)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let mut parser = Parser::new(tokenizer);
+
+        let x = ast::Node::indentifier(Token::build(Kind::ID, String::from("x")));
+        assert_eq!(
+            ast::Node::main(vec![
+                ast::Node::assign(
+                    x.clone(),
+                    ast::Node::constant(Token::build(Kind::Integer, String::from("1"))).unwrap(),
+                ),
+                ast::Node::stdout(x),
+            ]),
+            parser.parse().unwrap()
         )
     }
 }
@@ -1,193 +1,384 @@
-use ast::Node;
-use std::cmp::Eq;
-use std::cmp::Ord;
+use ast::Arity;
+use frame::Scope;
+use parser::ParseError;
 use std::cmp::Ordering;
+use std::fmt;
 use std::ops::{Add, Div, Mul, Not, Rem, Sub};
+use std::rc::Rc;
 use token::{Kind, Token};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Str(String),
     Int(i64),
+    Float(f64),
     Bool(bool),
-    Func(Vec<Node>, Vec<Node>),
-    List(Vec<Type>),
+    Func(Rc<Function>),
+    /// A function written in Rust, see `builtins`.
+    Builtin(&'static str),
+    List(List),
+    /// A `[...]` read as data, like the params of a `defn` given to a
+    /// macro.
+    Vector(List),
+    /// A name read as data, like `a` in `'(a 1)`.
+    Symbol(String),
 
     Nil,
 }
 
-impl Type {
-    pub fn from(token: &Token) -> Type {
-        match token.clone() {
-            Token {
-                kind: Kind::List,
-                value,
-            } => {
-                let v: Vec<&str> = value.as_str().split(',').collect();
-                let tokens: Vec<Token> = v
-                    .into_iter()
-                    .map(|t| {
-                        if let Some(result) = Kind::reserved(&String::from(t)) {
-                            Token::build(result, String::from(t))
+#[derive(Debug, PartialEq)]
+pub struct Function {
+    /// The name it was defined with, `fn` for lambdas.
+    pub name: String,
+    pub arities: Rc<Vec<Arity>>,
+    /// The scope the function was defined in.
+    pub env: Scope,
+}
+
+impl Function {
+    /// The clause to run for `count` arguments, one taking exactly `count`
+    /// before the variadic one.
+    pub fn arity(&self, count: usize) -> Result<&Arity, String> {
+        let exact = self
+            .arities
+            .iter()
+            .find(|arity| !arity.is_variadic() && arity.required() == count);
+        let variadic = || {
+            self.arities
+                .iter()
+                .find(|arity| arity.is_variadic() && arity.accepts(count))
+        };
+        match exact.or_else(variadic) {
+            Some(arity) => Ok(arity),
+            None => {
+                let expected: Vec<String> = self
+                    .arities
+                    .iter()
+                    .map(|arity| {
+                        if arity.is_variadic() {
+                            format!("at least {}", arity.required())
                         } else {
-                            let kind = Kind::classify(&t.chars().nth(0));
-                            Token::build(kind, String::from(t))
+                            arity.required().to_string()
                         }
                     })
                     .collect();
-
-                let types: Vec<Type> = tokens.iter().map(|t| Type::from(t)).collect();
-                Type::List(types)
+                let expected = expected.join(" or ");
+                let plural = match expected.as_ref() {
+                    "1" | "at least 1" => "",
+                    _ => "s",
+                };
+                Err(format!(
+                    "Value error: {} expects {} argument{}, got {}",
+                    self.name, expected, plural, count
+                ))
             }
+        }
+    }
+}
+
+/// An immutable list. Clones and `rest` share the items instead of copying
+/// them, so walking a long list one item at a time stays linear.
+#[derive(Debug, Clone)]
+pub struct List {
+    items: Rc<Vec<Type>>,
+    start: usize,
+}
+
+impl List {
+    pub fn new(items: Vec<Type>) -> Self {
+        List {
+            items: Rc::new(items),
+            start: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[Type] {
+        &self.items[self.start..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len() - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn first(&self) -> Option<&Type> {
+        self.items().first()
+    }
+
+    /// Every item but the first one.
+    pub fn rest(&self) -> List {
+        List {
+            items: self.items.clone(),
+            start: (self.start + 1).min(self.items.len()),
+        }
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &List) -> bool {
+        self.items() == other.items()
+    }
+}
+
+impl Type {
+    pub fn list(items: Vec<Type>) -> Type {
+        Type::List(List::new(items))
+    }
+
+    pub fn from(token: &Token) -> Result<Type, ParseError> {
+        match token.clone() {
             Token {
                 kind: Kind::Integer,
                 value,
-            } => Type::Int(value.parse::<i64>().expect("Invalid integer value.")),
+                ..
+            } => value
+                .parse::<i64>()
+                .map(Type::Int)
+                .map_err(|_| ParseError::Invalid {
+                    found: token.clone(),
+                    reason: String::from("invalid integer"),
+                }),
+            Token {
+                kind: Kind::Float,
+                value,
+                ..
+            } => value
+                .parse::<f64>()
+                .ok()
+                // `1.5e400` parses as infinity, which can't be read back.
+                .filter(|float| float.is_finite())
+                .map(Type::Float)
+                .ok_or_else(|| ParseError::Invalid {
+                    found: token.clone(),
+                    reason: String::from("invalid float"),
+                }),
             Token {
                 kind: Kind::Bolean,
                 value,
-            } => Type::Bool(value == "true"),
+                ..
+            } => Ok(Type::Bool(value == "true")),
             Token {
                 kind: Kind::Str,
                 value,
-            } => Type::Str(String::from(value)),
-            _ => Type::Nil,
+                ..
+            } => Ok(Type::Str(value)),
+            _ => Ok(Type::Nil),
         }
     }
 
-    pub fn to_string(self) -> String {
-        match self {
-            Type::Str(s) => format!("{}", s),
-            Type::Int(s) => format!("{}", s),
-            Type::Bool(s) => format!("{}", s),
-            Type::List(s) => {
-                let i: Vec<String> = s.into_iter().map(|t| t.to_string()).collect();
-                let items = i.join(" ");
-                format!("({})", items)
+    /// The value written as code, reading it back gives the same value.
+    /// Used to turn what a macro returns into code to run.
+    pub fn source(&self) -> String {
+        fn items(list: &List) -> String {
+            let items: Vec<String> = list.items().iter().map(|item| item.source()).collect();
+            items.join(" ")
+        }
+
+        match *self {
+            Type::Str(ref s) => {
+                let mut written = String::from("\"");
+                for character in s.chars() {
+                    match character {
+                        '"' => written.push_str("\\\""),
+                        '\\' => written.push_str("\\\\"),
+                        '\n' => written.push_str("\\n"),
+                        '\t' => written.push_str("\\t"),
+                        other => written.push(other),
+                    }
+                }
+                written.push('"');
+                written
             }
-            _ => String::new(),
+            Type::Builtin(name) => name.to_string(),
+            Type::List(ref list) => match list.items() {
+                [Type::Symbol(ref quote), ref quoted] if quote == "quote" => {
+                    format!("'{}", quoted.source())
+                }
+                _ => format!("({})", items(list)),
+            },
+            Type::Vector(ref list) => format!("[{}]", items(list)),
+            Type::Nil => String::from("nil"),
+            _ => self.to_string(),
         }
     }
 
+    /// Whether the value counts as true in a condition. Only `false`,
+    /// `nil` and numbers that aren't positive count as false.
     pub fn as_bool(&self) -> bool {
-        match self.clone() {
-            Type::Int(s) => (s > 0),
+        match *self {
+            Type::Int(s) => s > 0,
+            Type::Float(s) => s > 0.0,
             Type::Bool(s) => s,
             Type::Nil => false,
-            _ => panic!("Value error: type {:?} cannot be used as boolean", self),
+            _ => true,
         }
     }
 }
 
-impl PartialOrd for Type {
-    fn partial_cmp(&self, other: &Type) -> Option<Ordering> {
-        Some(self.cmp(other))
+/// The items of a list as printed, a `nil` in a list can't be left blank.
+fn items(list: &List) -> String {
+    let items: Vec<String> = list
+        .items()
+        .iter()
+        .map(|item| match *item {
+            Type::Nil => String::from("nil"),
+            _ => item.to_string(),
+        })
+        .collect();
+    items.join(" ")
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Str(ref s) => write!(f, "{}", s),
+            Type::Int(s) => write!(f, "{}", s),
+            // Debug keeps the `.0` of whole floats, so they read back as
+            // floats and not ints.
+            Type::Float(s) => write!(f, "{:?}", s),
+            Type::Bool(s) => write!(f, "{}", s),
+            Type::Symbol(ref s) => write!(f, "{}", s),
+            Type::Func(..) => write!(f, "#<fn>"),
+            Type::Builtin(ref s) => write!(f, "#<fn {}>", s),
+            Type::List(ref s) => write!(f, "({})", items(s)),
+            Type::Vector(ref s) => write!(f, "[{}]", items(s)),
+            _ => Ok(()),
+        }
     }
 }
 
-impl Ord for Type {
-    fn cmp(&self, other: &Type) -> Ordering {
-        match (self.clone(), other.clone()) {
-            (Type::Bool(s), Type::Bool(o)) => s.cmp(&o),
-            (Type::Int(s), Type::Int(o)) => s.cmp(&o),
-            _ => panic!("Operation error: invalid comparison {:?} {:?}", self, other),
+impl Type {
+    /// How `self` orders against `other`, `None` when either is a NaN,
+    /// which is neither less, greater nor equal to anything. Only numbers
+    /// and booleans can be compared.
+    pub fn compare(&self, other: &Type) -> Result<Option<Ordering>, String> {
+        match (self, other) {
+            (&Type::Bool(s), &Type::Bool(o)) => Ok(Some(s.cmp(&o))),
+            (&Type::Int(s), &Type::Int(o)) => Ok(Some(s.cmp(&o))),
+            _ => match promote(self, other) {
+                Some((s, o)) => Ok(s.partial_cmp(&o)),
+                None => Err(format!(
+                    "Operation error: can't compare {} with {}",
+                    self.source(),
+                    other.source()
+                )),
+            },
         }
     }
 }
 
-impl Eq for Type {}
+/// Both operands as floats when at least one of them is a float, ints are
+/// promoted so `(+ 1 0.5)` is `1.5`.
+fn promote(left: &Type, right: &Type) -> Option<(f64, f64)> {
+    match (left, right) {
+        (&Type::Float(s), &Type::Float(o)) => Some((s, o)),
+        (&Type::Int(s), &Type::Float(o)) => Some((s as f64, o)),
+        (&Type::Float(s), &Type::Int(o)) => Some((s, o as f64)),
+        _ => None,
+    }
+}
 
 impl Add for Type {
-    type Output = Type;
-
-    fn add(self, other: Type) -> Type {
-        match (self.clone(), other.clone()) {
-            (Type::Int(s), Type::Int(o)) => Type::Int(s + o),
-            _ => panic!(
-                "Operation error: invalid add operation between
-                       {:?} and {:?}",
-                self, other
-            ),
-        }
+    type Output = Result<Type, String>;
+
+    fn add(self, other: Type) -> Result<Type, String> {
+        arithmetic("add", &self, &other, i64::checked_add, |s, o| s + o)
     }
 }
 
 impl Sub for Type {
-    type Output = Type;
-
-    fn sub(self, other: Type) -> Type {
-        match (self.clone(), other.clone()) {
-            (Type::Int(s), Type::Int(o)) => Type::Int(s - o),
-            _ => panic!(
-                "Operation error: invalid add operation between
-                       {:?} and {:?}",
-                self, other
-            ),
-        }
+    type Output = Result<Type, String>;
+
+    fn sub(self, other: Type) -> Result<Type, String> {
+        arithmetic("subtract", &self, &other, i64::checked_sub, |s, o| s - o)
     }
 }
 
 impl Mul for Type {
-    type Output = Type;
-
-    fn mul(self, other: Type) -> Type {
-        match (self.clone(), other.clone()) {
-            (Type::Int(s), Type::Int(o)) => Type::Int(s * o),
-            _ => panic!(
-                "Operation error: invalid add operation between
-                       {:?} and {:?}",
-                self, other
-            ),
-        }
+    type Output = Result<Type, String>;
+
+    fn mul(self, other: Type) -> Result<Type, String> {
+        arithmetic("multiply", &self, &other, i64::checked_mul, |s, o| s * o)
     }
 }
 
 impl Div for Type {
-    type Output = Type;
-
-    fn div(self, other: Type) -> Type {
-        match (self.clone(), other.clone()) {
-            (Type::Int(s), Type::Int(o)) => Type::Int(s / o),
-            _ => panic!(
-                "Operation error: invalid add operation between
-                       {:?} and {:?}",
-                self, other
-            ),
-        }
+    type Output = Result<Type, String>;
+
+    fn div(self, other: Type) -> Result<Type, String> {
+        arithmetic("divide", &self, &other, i64::checked_div, |s, o| s / o)
     }
 }
 
 impl Rem for Type {
-    type Output = Type;
-
-    fn rem(self, other: Type) -> Type {
-        match (self.clone(), other.clone()) {
-            (Type::Int(s), Type::Int(o)) => Type::Int(s % o),
-            _ => panic!(
-                "Operation error: invalid add operation between
-                       {:?} and {:?}",
-                self, other
-            ),
-        }
+    type Output = Result<Type, String>;
+
+    fn rem(self, other: Type) -> Result<Type, String> {
+        arithmetic(
+            "take the remainder of",
+            &self,
+            &other,
+            i64::checked_rem,
+            |s, o| s % o,
+        )
     }
 }
 
 impl Not for Type {
-    type Output = Type;
+    type Output = Result<Type, String>;
 
-    fn not(self) -> Type {
+    fn not(self) -> Result<Type, String> {
         match self {
-            Type::Bool(o) => Type::Bool(!o),
-            Type::Str(o) => Type::Str(o),
-            Type::Int(o) => Type::Int(o * -1),
-            _ => panic!("Operation error: invalid not operation"),
+            Type::Bool(o) => Ok(Type::Bool(!o)),
+            Type::Str(o) => Ok(Type::Str(o)),
+            Type::Int(o) => Ok(Type::Int(-o)),
+            Type::Float(o) => Ok(Type::Float(-o)),
+            other => Err(format!("Operation error: can't negate {}", other.source())),
         }
     }
 }
 
+/// Combines two numbers with `int` when both are ints and with `float`
+/// when either is a float. An int result that doesn't fit, or a division
+/// by zero, is an error rather than a panic.
+fn arithmetic(
+    verb: &str,
+    left: &Type,
+    right: &Type,
+    int: fn(i64, i64) -> Option<i64>,
+    float: fn(f64, f64) -> f64,
+) -> Result<Type, String> {
+    match (left, right) {
+        (&Type::Int(s), &Type::Int(o)) => int(s, o).map(Type::Int).ok_or_else(|| {
+            if o == 0 {
+                format!("Operation error: can't {} {} by zero", verb, s)
+            } else {
+                format!(
+                    "Operation error: can't {} {} and {} without overflowing",
+                    verb, s, o
+                )
+            }
+        }),
+        _ => match promote(left, right) {
+            Some((s, o)) => Ok(Type::Float(float(s, o))),
+            None => Err(format!(
+                "Operation error: can't {} {} and {}",
+                verb,
+                left.source(),
+                right.source()
+            )),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ast::Node;
+    use frame::{Env, Frame};
 
     #[test]
     fn test_type_int_to_string() {
@@ -195,6 +386,144 @@ mod tests {
         assert_eq!(String::from("1"), type_.to_string())
     }
 
+    #[test]
+    fn test_type_float_to_string() {
+        assert_eq!(String::from("0.25"), Type::Float(0.25).to_string());
+        assert_eq!(String::from("-2.0"), Type::Float(-2.0).to_string());
+        assert_eq!(String::from("1e-7"), Type::Float(1e-7).to_string());
+    }
+
+    #[test]
+    fn test_type_float_from_token() {
+        let token = Token::build(Kind::Float, String::from("-1.5e3"));
+        assert_eq!(Type::Float(-1500.0), Type::from(&token).unwrap());
+    }
+
+    #[test]
+    fn test_type_float_promotion() {
+        assert_eq!(Ok(Type::Float(1.5)), Type::Int(1) + Type::Float(0.5));
+        assert_eq!(Ok(Type::Float(-0.5)), Type::Float(0.5) - Type::Int(1));
+        assert_eq!(Ok(Type::Float(5.0)), Type::Int(2) * Type::Float(2.5));
+        assert_eq!(Ok(Type::Float(2.5)), Type::Int(5) / Type::Float(2.0));
+        assert_eq!(Ok(Type::Int(2)), Type::Int(5) / Type::Int(2));
+    }
+
+    #[test]
+    fn test_type_invalid_arithmetic() {
+        assert_eq!(
+            Err(String::from("Operation error: can't add \"a\" and 1")),
+            Type::Str(String::from("a")) + Type::Int(1)
+        );
+        assert_eq!(
+            Err(String::from("Operation error: can't divide 1 by zero")),
+            Type::Int(1) / Type::Int(0)
+        );
+        assert!((Type::Int(i64::MAX) + Type::Int(1)).is_err());
+    }
+
+    #[test]
+    fn test_type_float_overflow_from_token() {
+        let token = Token::build(Kind::Float, String::from("1.5e400"));
+        assert!(Type::from(&token).is_err());
+    }
+
+    #[test]
+    fn test_type_float_cmp() {
+        assert_eq!(
+            Ok(Some(Ordering::Less)),
+            Type::Int(1).compare(&Type::Float(1.5))
+        );
+        assert_eq!(
+            Ok(Some(Ordering::Less)),
+            Type::Float(-0.5).compare(&Type::Int(0))
+        );
+        assert_eq!(
+            Ok(Some(Ordering::Equal)),
+            Type::Float(2.0).compare(&Type::Int(2))
+        );
+    }
+
+    #[test]
+    fn test_type_compare_nan() {
+        assert_eq!(Ok(None), Type::Float(f64::NAN).compare(&Type::Int(1)));
+        assert_eq!(Ok(None), Type::Float(1.0).compare(&Type::Float(f64::NAN)));
+    }
+
+    #[test]
+    fn test_type_compare_invalid() {
+        assert_eq!(
+            Err(String::from("Operation error: can't compare \"a\" with 1")),
+            Type::Str(String::from("a")).compare(&Type::Int(1))
+        );
+    }
+
+    #[test]
+    fn test_type_as_bool() {
+        assert!(Type::Int(1).as_bool());
+        assert!(!Type::Float(0.0).as_bool());
+        assert!(!Type::Nil.as_bool());
+        assert!(Type::Builtin("list").as_bool());
+        assert!(Type::Symbol(String::from("a")).as_bool());
+        assert!(Type::Vector(List::new(vec![])).as_bool());
+        assert!(Type::Str(String::new()).as_bool());
+    }
+
+    #[test]
+    fn test_function_arity() {
+        let param = |name: &str| Node::indentifier(Token::build(Kind::ID, String::from(name)));
+        let function = Function {
+            name: String::from("f"),
+            arities: Rc::new(vec![
+                Arity::new(vec![param("a")], vec![]),
+                Arity::new(vec![param("a"), param("b"), param("&"), param("c")], vec![]),
+            ]),
+            env: Scope::Strong(Env::new(Frame::new())),
+        };
+
+        assert_eq!(Ok(&function.arities[0]), function.arity(1));
+        assert_eq!(Ok(&function.arities[1]), function.arity(4));
+        assert_eq!(
+            Err(String::from(
+                "Value error: f expects 1 or at least 2 arguments, got 0"
+            )),
+            function.arity(0)
+        );
+    }
+
+    #[test]
+    fn test_function_arity_prefers_exact() {
+        let param = |name: &str| Node::indentifier(Token::build(Kind::ID, String::from(name)));
+        let function = Function {
+            name: String::from("f"),
+            arities: Rc::new(vec![
+                Arity::new(vec![param("&"), param("xs")], vec![]),
+                Arity::new(vec![param("a")], vec![]),
+            ]),
+            env: Scope::Strong(Env::new(Frame::new())),
+        };
+
+        assert_eq!(Ok(&function.arities[1]), function.arity(1));
+        assert_eq!(Ok(&function.arities[0]), function.arity(0));
+        assert_eq!(Ok(&function.arities[0]), function.arity(2));
+    }
+
+    #[test]
+    fn test_type_source() {
+        let code = Type::list(vec![
+            Type::Symbol(String::from("print")),
+            Type::Str(String::from("say \"oi\"\n")),
+            Type::Vector(List::new(vec![Type::Float(1.0)])),
+            Type::list(vec![
+                Type::Symbol(String::from("quote")),
+                Type::Symbol(String::from("a")),
+            ]),
+        ]);
+        assert_eq!(
+            String::from("(print \"say \\\"oi\\\"\\n\" [1.0] 'a)"),
+            code.source()
+        );
+    }
+
     #[test]
     fn test_type_bool_to_string() {
         let type_ = Type::Bool(true);
@@ -203,7 +532,7 @@ mod tests {
 
     #[test]
     fn test_type_list_to_string() {
-        let type_ = Type::List(vec![Type::Int(1), Type::Str(String::from("oi"))]);
+        let type_ = Type::list(vec![Type::Int(1), Type::Str(String::from("oi"))]);
         assert_eq!(String::from("(1 oi)"), type_.to_string())
     }
 
@@ -216,12 +545,12 @@ mod tests {
     #[test]
     fn test_type_int_as_bool() {
         let type_ = Type::Int(1);
-        assert_eq!(true, type_.as_bool())
+        assert!(type_.as_bool())
     }
 
     #[test]
     fn test_type_nil_as_bool() {
         let type_ = Type::Nil;
-        assert_eq!(false, type_.as_bool())
+        assert!(!type_.as_bool())
     }
 }
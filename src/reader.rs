@@ -0,0 +1,469 @@
+use parser::ParseError;
+use primitive::{List, Type};
+use token::{Kind, Span, Token, Tokenizer};
+
+/// The shape of a datum, without saying what it means as code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Form {
+    Symbol(String),
+    Literal(Type),
+    List(Vec<Datum>),
+    Vector(Vec<Datum>),
+}
+
+/// A form read from the source. `token` is the token the datum starts
+/// with, its kind tells which special form a symbol names, and `end` is the
+/// one it ends with, the `)` or `]` that closes a list or vector.
+#[derive(Debug, Clone)]
+pub struct Datum {
+    pub form: Form,
+    pub token: Token,
+    pub end: Token,
+    pub span: Span,
+}
+
+// Like nodes, datums are compared by what they hold, not by where they were
+// written.
+impl PartialEq for Datum {
+    fn eq(&self, other: &Datum) -> bool {
+        self.form == other.form
+    }
+}
+
+impl Datum {
+    fn build(form: Form, token: Token) -> Self {
+        Datum {
+            form,
+            span: token.span,
+            end: token.clone(),
+            token,
+        }
+    }
+
+    /// The datum for a value built at runtime, like the code a macro
+    /// returns. Every part of it is placed at `span`.
+    pub fn from_value(value: &Type, span: Span) -> Self {
+        let token = |kind, value: String| Token::build(kind, value).at(span);
+        let sequence = |form, open: &str, close: &str| {
+            let kind = match form {
+                Form::List(_) => (Kind::GroupBegin, Kind::GroupEnd),
+                _ => (Kind::ArgsBegin, Kind::ArgsEnd),
+            };
+            Datum {
+                form,
+                token: token(kind.0, String::from(open)),
+                end: token(kind.1, String::from(close)),
+                span,
+            }
+        };
+        let items = |items: &List| {
+            items
+                .items()
+                .iter()
+                .map(|item| Datum::from_value(item, span))
+                .collect()
+        };
+
+        match *value {
+            Type::List(ref list) => sequence(Form::List(items(list)), "(", ")"),
+            Type::Vector(ref list) => sequence(Form::Vector(items(list)), "[", "]"),
+            Type::Symbol(ref name) => {
+                let kind = Kind::reserved(name).unwrap_or(Kind::ID);
+                Datum::build(Form::Symbol(name.clone()), token(kind, name.clone()))
+            }
+            Type::Builtin(name) => Datum::build(
+                Form::Symbol(String::from(name)),
+                token(Kind::ID, String::from(name)),
+            ),
+            Type::Str(ref text) => {
+                Datum::build(Form::Literal(value.clone()), token(Kind::Str, text.clone()))
+            }
+            Type::Int(number) => Datum::build(
+                Form::Literal(value.clone()),
+                token(Kind::Integer, number.to_string()),
+            ),
+            Type::Float(number) => Datum::build(
+                Form::Literal(value.clone()),
+                token(Kind::Float, format!("{:?}", number)),
+            ),
+            Type::Bool(boolean) => Datum::build(
+                Form::Literal(value.clone()),
+                token(Kind::Bolean, boolean.to_string()),
+            ),
+            Type::Nil => Datum::build(Form::Literal(Type::Nil), token(Kind::ID, value.source())),
+            // Values with no literal syntax, like functions, are kept as
+            // they are.
+            _ => Datum::build(
+                Form::Literal(value.clone()),
+                token(Kind::Alphanum, value.source()),
+            ),
+        }
+    }
+
+    /// The datum as a value: lists and vectors nest and names become
+    /// symbols.
+    pub fn to_value(&self) -> Type {
+        match self.form {
+            Form::Symbol(ref name) => Type::Symbol(name.clone()),
+            Form::Literal(ref value) => value.clone(),
+            Form::List(ref items) => Type::list(items.iter().map(Datum::to_value).collect()),
+            Form::Vector(ref items) => {
+                Type::Vector(List::new(items.iter().map(Datum::to_value).collect()))
+            }
+        }
+    }
+}
+
+/// Reads the source into datums. Syntax errors don't stop it, they are
+/// recorded and the datum is read as well as it can be, so every error in
+/// the source gets reported.
+pub struct Reader {
+    tokenizer: Tokenizer,
+    /// How many `(` are open around the datum being read.
+    depth: usize,
+    errors: Vec<ParseError>,
+}
+
+impl Reader {
+    pub fn new(tokenizer: Tokenizer) -> Self {
+        Reader {
+            tokenizer,
+            depth: 0,
+            errors: vec![],
+        }
+    }
+
+    /// The errors found since the last call, in the order they were found.
+    pub fn errors(&mut self) -> Vec<ParseError> {
+        self.errors.drain(..).collect()
+    }
+
+    /// Reads the next datum, `None` once the source is over.
+    pub fn read(&mut self) -> Option<Datum> {
+        loop {
+            let token = self.lookahead();
+            match token.kind {
+                Kind::EOF => return None,
+                Kind::GroupEnd | Kind::ArgsEnd => self.skip(token),
+                _ => return Some(self.datum()),
+            }
+        }
+    }
+
+    fn lookahead(&mut self) -> Token {
+        self.tokenizer
+            .peek()
+            .unwrap_or_else(|| Token::build(Kind::EOF, String::new()))
+    }
+
+    /// Records `token` as out of place and moves past it.
+    fn skip(&mut self, token: Token) {
+        let _ = self.tokenizer.consume(token.kind.clone());
+        self.errors.push(ParseError::Invalid {
+            found: token,
+            reason: String::from("unexpected token"),
+        });
+    }
+
+    /// A datum standing for one that couldn't be read, its error is
+    /// already recorded.
+    fn placeholder(token: Token) -> Datum {
+        let token = Token {
+            kind: Kind::Invalid,
+            ..token
+        };
+        Datum::build(Form::Literal(Type::Nil), token)
+    }
+
+    fn datum(&mut self) -> Datum {
+        let token = self.lookahead();
+        match token.kind {
+            Kind::GroupBegin => self.sequence(Kind::GroupEnd),
+            Kind::ArgsBegin => self.sequence(Kind::ArgsEnd),
+            Kind::List | Kind::Quasiquote | Kind::Unquote | Kind::UnquoteSplice => self.sugar(),
+            Kind::Str | Kind::Integer | Kind::Float | Kind::Bolean | Kind::Invalid => {
+                let literal = self
+                    .tokenizer
+                    .consume(token.kind.clone())
+                    .and_then(|token| Type::from(&token));
+                match literal {
+                    Ok(value) => Datum::build(Form::Literal(value), token),
+                    Err(error) => {
+                        self.errors.push(error);
+                        Reader::placeholder(token)
+                    }
+                }
+            }
+            _ => {
+                let _ = self.tokenizer.consume(token.kind.clone());
+                if token.kind == Kind::ID && token.value == "nil" {
+                    Datum::build(Form::Literal(Type::Nil), token)
+                } else {
+                    Datum::build(Form::Symbol(token.value.clone()), token)
+                }
+            }
+        }
+    }
+
+    /// Reads a `(...)` list or a `[...]` vector, closed by `end`. A stray
+    /// `]` in a list is skipped and a `)` in a vector closes it, as its `]`
+    /// was likely forgotten.
+    fn sequence(&mut self, end: Kind) -> Datum {
+        let open = self.lookahead();
+        let _ = self.tokenizer.consume(open.kind.clone());
+        if end == Kind::GroupEnd {
+            self.depth += 1;
+        }
+
+        let mut items = vec![];
+        let close = loop {
+            let token = self.lookahead();
+            if token.kind == end {
+                let _ = self.tokenizer.consume(end.clone());
+                break Some(token);
+            }
+            match token.kind {
+                // The sequence is unclosed at the end of file, unless an error
+                // already ran into it, like an unterminated string.
+                Kind::EOF
+                    if self.errors.last().is_some_and(|error| {
+                        error.span().end.offset >= token.span.start.offset
+                    }) =>
+                {
+                    break Some(token)
+                }
+                Kind::EOF => break None,
+                // A `(` at the start of a line begins the next top level
+                // form, so one missing `)` doesn't swallow the rest of the
+                // file.
+                Kind::GroupBegin if token.span.start.column == 1 => break None,
+                Kind::GroupEnd => {
+                    self.errors.push(ParseError::Unexpected {
+                        expected: end.clone(),
+                        found: token.clone(),
+                    });
+                    // Left for the enclosing list to close, if there is one.
+                    if self.depth == 0 {
+                        let _ = self.tokenizer.consume(Kind::GroupEnd);
+                    }
+                    break Some(token);
+                }
+                Kind::ArgsEnd => self.skip(token),
+                _ => items.push(self.datum()),
+            }
+        };
+
+        // An unclosed sequence ends with its last item and is reported at
+        // its opening token, items found missing in it are reported there
+        // as well.
+        let (last, close) = match close {
+            Some(close) => (close.span, close),
+            None => {
+                self.errors
+                    .push(ParseError::Unclosed { open: open.clone() });
+                (
+                    items.last().map_or(open.span, |item| item.span),
+                    open.clone(),
+                )
+            }
+        };
+        let form = match end {
+            Kind::GroupEnd => {
+                self.depth -= 1;
+                Form::List(items)
+            }
+            _ => Form::Vector(items),
+        };
+        Datum {
+            form,
+            span: open.span.to(last),
+            token: open,
+            end: close,
+        }
+    }
+
+    /// Reads `'x`, `` `x ``, `~x` and `~@x` as the lists `(quote x)`,
+    /// `(quasiquote x)`, `(unquote x)` and `(unquote-splicing x)`.
+    fn sugar(&mut self) -> Datum {
+        let token = self.lookahead();
+        let _ = self.tokenizer.consume(token.kind.clone());
+        let (kind, name) = match token.kind {
+            Kind::List => (Kind::Quote, "quote"),
+            Kind::Quasiquote => (Kind::ID, "quasiquote"),
+            Kind::Unquote => (Kind::ID, "unquote"),
+            _ => (Kind::ID, "unquote-splicing"),
+        };
+        let head = Datum::build(
+            Form::Symbol(String::from(name)),
+            Token::build(kind, String::from(name)).at(token.span),
+        );
+
+        let next = self.lookahead();
+        let quoted = match next.kind {
+            Kind::EOF => {
+                self.errors.push(ParseError::Unexpected {
+                    expected: Kind::GroupEnd,
+                    found: next.clone(),
+                });
+                Reader::placeholder(next)
+            }
+            Kind::GroupEnd | Kind::ArgsEnd => {
+                self.errors.push(ParseError::Invalid {
+                    found: next.clone(),
+                    reason: String::from("unexpected token"),
+                });
+                Reader::placeholder(next)
+            }
+            _ => self.datum(),
+        };
+        Datum {
+            span: token.span.to(quoted.span),
+            end: quoted.end.clone(),
+            form: Form::List(vec![head, quoted]),
+            token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> (Vec<Datum>, Vec<ParseError>) {
+        let mut reader = Reader::new(Tokenizer::new(String::from(text)));
+        let mut datums = vec![];
+        while let Some(datum) = reader.read() {
+            datums.push(datum);
+        }
+        (datums, reader.errors())
+    }
+
+    fn symbol(name: &str) -> Type {
+        Type::Symbol(String::from(name))
+    }
+
+    #[test]
+    fn test_read_list() {
+        let (datums, errors) = read("(+ x [1 \"a\"] nil)");
+        assert!(errors.is_empty());
+        assert_eq!(
+            Type::list(vec![
+                symbol("+"),
+                symbol("x"),
+                Type::Vector(List::new(vec![Type::Int(1), Type::Str(String::from("a"))])),
+                Type::Nil,
+            ]),
+            datums[0].to_value()
+        );
+        match datums[0].form {
+            Form::List(ref items) => assert_eq!(Kind::Operator, items[0].token.kind),
+            _ => panic!("expected a list"),
+        }
+        assert_eq!(17, datums[0].span.end.column - datums[0].span.start.column);
+    }
+
+    #[test]
+    fn test_read_quote_sugar() {
+        let (datums, _) = read("'(a `b ~c ~@d)");
+        assert_eq!(
+            Type::list(vec![
+                symbol("quote"),
+                Type::list(vec![
+                    symbol("a"),
+                    Type::list(vec![symbol("quasiquote"), symbol("b")]),
+                    Type::list(vec![symbol("unquote"), symbol("c")]),
+                    Type::list(vec![symbol("unquote-splicing"), symbol("d")]),
+                ]),
+            ]),
+            datums[0].to_value()
+        );
+        assert_eq!(Kind::List, datums[0].token.kind);
+    }
+
+    #[test]
+    fn test_read_several_datums() {
+        let (datums, _) = read("(def x 1) x ; comment\n 2");
+        assert_eq!(3, datums.len());
+        assert_eq!(Type::Int(2), datums[2].to_value());
+    }
+
+    #[test]
+    fn test_read_recovers_from_errors() {
+        let (datums, errors) = read("(+ 1 ] 2) ]\n(print \"x)");
+        assert_eq!(2, datums.len());
+        assert_eq!(
+            Type::list(vec![symbol("+"), Type::Int(1), Type::Int(2)]),
+            datums[0].to_value()
+        );
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            vec![
+                "Syntax error: unexpected token `]` at line 1, column 6",
+                "Syntax error: unexpected token `]` at line 1, column 11",
+                "Syntax error: unterminated string `\"` at line 2, column 8",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_read_resyncs_at_line_start() {
+        let (datums, errors) = read("(defn f [x]\n  (+ x 1\n(def y 2)");
+        assert_eq!(2, datums.len());
+        assert_eq!(
+            Type::list(vec![symbol("def"), symbol("y"), Type::Int(2)]),
+            datums[1].to_value()
+        );
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(
+            vec![
+                "Syntax error: unclosed `(` at line 2, column 3",
+                "Syntax error: unclosed `(` at line 1, column 1",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_read_vector_closed_by_paren() {
+        let (datums, errors) = read("(print [1 2)");
+        assert_eq!(
+            vec![ParseError::Unexpected {
+                expected: Kind::ArgsEnd,
+                found: Token::build(Kind::GroupEnd, String::from(")")),
+            }],
+            errors
+        );
+        assert_eq!(
+            Type::list(vec![
+                symbol("print"),
+                Type::Vector(List::new(vec![Type::Int(1), Type::Int(2)])),
+            ]),
+            datums[0].to_value()
+        );
+    }
+
+    #[test]
+    fn test_datum_from_value() {
+        let value = Type::list(vec![
+            symbol("if"),
+            Type::Bool(true),
+            Type::Vector(List::new(vec![Type::Float(1.5)])),
+            Type::Builtin("first"),
+        ]);
+        let datum = Datum::from_value(&value, Span::default());
+        assert_eq!(
+            Type::list(vec![
+                symbol("if"),
+                Type::Bool(true),
+                Type::Vector(List::new(vec![Type::Float(1.5)])),
+                symbol("first"),
+            ]),
+            datum.to_value()
+        );
+        match datum.form {
+            Form::List(ref items) => assert_eq!(Kind::If, items[0].token.kind),
+            _ => panic!("expected a list"),
+        }
+    }
+}
@@ -1,21 +1,71 @@
 use interpreter::Interpreter;
-use parser::Parser;
+use parser::{ParseError, Parser, Warning};
+use primitive::Type;
 use token::Tokenizer;
 
 pub struct Repl {
     interpreter: Interpreter,
+    warnings: Vec<Warning>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl::new()
+    }
 }
 
 impl Repl {
     pub fn new() -> Self {
         Repl {
             interpreter: Interpreter::new(),
+            warnings: vec![],
         }
     }
 
-    pub fn eval(&mut self, source: String) -> String {
+    /// Runs `source` and returns the printed value of its last form, or
+    /// the error message that stopped it. Each form is analyzed and run in
+    /// turn, so it can call the macros the forms before it defined. After
+    /// a syntax error the rest is only analyzed, to report its errors too.
+    pub fn run(&mut self, source: String) -> Result<String, String> {
         let tokenizer = Tokenizer::new(source);
         let mut parser = Parser::new(tokenizer);
-        self.interpreter.eval(parser.parse())
+        self.warnings = vec![];
+        let forms = parser.read().map_err(|errors| report(&errors))?;
+
+        let mut result = Type::Nil;
+        let mut errors = vec![];
+        for datum in &forms {
+            let node = parser.analyze_with(datum, self.interpreter.macro_names());
+            self.warnings = parser.warnings().to_vec();
+            match node {
+                Ok(node) if errors.is_empty() => result = self.interpreter.run(node)?,
+                Ok(_) => {}
+                Err(found) => errors.extend(found),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(report(&errors));
+        }
+        Ok(result.to_string())
     }
+
+    /// The warnings found in the source of the last `run`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn eval(&mut self, source: String) -> String {
+        match self.run(source) {
+            Ok(result) => result,
+            Err(error) => error,
+        }
+    }
+}
+
+fn report(errors: &[ParseError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
 }
@@ -1,9 +1,16 @@
+use parser::ParseError;
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Kind {
     Integer,
+    Float,
     Str,
     Bolean,
     List,
+    Quasiquote,
+    Unquote,
+    UnquoteSplice,
     Alphanum,
 
     Operator,
@@ -24,12 +31,24 @@ pub enum Kind {
     StdOut,
 
     If,
+    Cond,
     When,
+    Do,
     FnDefine,
+    MacroDefine,
+    Lambda,
     VarDefine,
+    Let,
+    Loop,
+    Recur,
+    Match,
+    Quote,
 
     EndLine,
     EOF,
+
+    /// Text that can't be read as a token, like an unterminated string.
+    Invalid,
 }
 
 impl Kind {
@@ -42,10 +61,12 @@ impl Kind {
                 ')' => Kind::GroupEnd,
                 '[' => Kind::ArgsBegin,
                 ']' => Kind::ArgsEnd,
-                ' ' => Kind::Space,
+                ' ' | '\t' | '\r' => Kind::Space,
                 '\n' => Kind::EndLine,
                 '"' => Kind::Str,
                 '\'' => Kind::List,
+                '`' => Kind::Quasiquote,
+                '~' => Kind::Unquote,
                 '+' | '-' | '*' | '/' => Kind::Operator,
                 '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => Kind::Integer,
                 _ => Kind::Alphanum,
@@ -54,14 +75,23 @@ impl Kind {
         }
     }
 
-    pub fn reserved(word: &String) -> Option<Kind> {
-        match word.as_ref() {
+    pub fn reserved(word: &str) -> Option<Kind> {
+        match word {
             "defn" => Some(Kind::FnDefine),
+            "defmacro" => Some(Kind::MacroDefine),
+            "fn" => Some(Kind::Lambda),
             "def" => Some(Kind::VarDefine),
-            "inc" | "rem" | "dec" => Some(Kind::Operator),
+            "let" => Some(Kind::Let),
+            "loop" => Some(Kind::Loop),
+            "recur" => Some(Kind::Recur),
+            "match" => Some(Kind::Match),
+            "quote" => Some(Kind::Quote),
+            "+" | "-" | "*" | "/" | "inc" | "rem" | "dec" => Some(Kind::Operator),
             "print" => Some(Kind::StdOut),
             "if" => Some(Kind::If),
+            "cond" => Some(Kind::Cond),
             "when" => Some(Kind::When),
+            "do" => Some(Kind::Do),
             "and" | "or" | "not" => Some(Kind::Logical),
             "=" | "/=" | ">" | "<" | "<=" | ">=" | "max" | "min" => Some(Kind::Comparison),
             "true" | "false" => Some(Kind::Bolean),
@@ -70,38 +100,127 @@ impl Kind {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A point in the source text. `offset` is in bytes, `line` and `column`
+/// start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The region of the source text a token or node was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+
+    /// Span covering from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.start.line, self.start.column)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Token {
     pub kind: Kind,
     pub value: String,
+    pub span: Span,
 }
 
 impl Token {
     pub fn build(kind: Kind, value: String) -> Token {
-        Token { kind, value }
+        Token {
+            kind,
+            value,
+            span: Span::default(),
+        }
+    }
+
+    pub fn at(mut self, span: Span) -> Token {
+        self.span = span;
+        self
+    }
+}
+
+// Spans only say where a token came from, two tokens are the same when
+// their kind and value match.
+impl PartialEq for Token {
+    fn eq(&self, other: &Token) -> bool {
+        self.kind == other.kind && self.value == other.value
     }
 }
 
 #[derive(Debug)]
 pub struct Tokenizer {
     pub text: String,
+    /// Byte offset of the next char to read.
     pub position: usize,
+    line: usize,
+    column: usize,
+    /// Span of the last token handed out by `consume`.
+    pub consumed: Span,
+    /// One token lookahead, read by `advance` and taken by `consume`.
     current: Option<Token>,
+    /// Why the last `Kind::Invalid` token couldn't be read.
+    invalid: String,
 }
 
 impl Tokenizer {
     pub fn new(text: String) -> Self {
         Tokenizer {
-            text: text,
+            text,
             position: 0,
+            line: 1,
+            column: 1,
+            consumed: Span::default(),
             current: None,
+            invalid: String::new(),
         }
     }
 }
 
 impl Tokenizer {
     pub fn current(&self) -> Option<char> {
-        self.text.chars().nth(self.position)
+        self.text[self.position..].chars().next()
+    }
+
+    fn ahead(&self, count: usize) -> Option<char> {
+        self.text[self.position..].chars().nth(count)
+    }
+
+    /// Moves past the current char, keeping line and column up to date.
+    fn bump(&mut self) {
+        if let Some(character) = self.current() {
+            self.position += character.len_utf8();
+            if character == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Line, column and byte offset of the next char to read.
+    pub fn location(&self) -> Position {
+        Position {
+            offset: self.position,
+            line: self.line,
+            column: self.column,
+        }
     }
 
     pub fn advance(&mut self) -> &mut Self {
@@ -115,130 +234,261 @@ impl Tokenizer {
         self.current.clone()
     }
 
+    /// The next token, without consuming it.
     pub fn peek(&mut self) -> Option<Token> {
-        let curr_position = self.position.clone();
-        if self.position == 0 {
-            self.position += 1;
-        }
-        let next = self.next();
-        self.position = curr_position;
-        next
+        self.advance().get()
     }
 
-    pub fn consume(&mut self, expect_kind: Kind) -> Token {
-        if let Some(token) = self.get() {
+    pub fn consume(&mut self, expect_kind: Kind) -> Result<Token, ParseError> {
+        let token = match self.get() {
+            Some(token) => token,
+            None => Token::build(Kind::EOF, String::new())
+                .at(Span::new(self.location(), self.location())),
+        };
+        if token.kind == Kind::Invalid {
             self.current = None;
-            if token.kind != expect_kind {
-                panic!(
-                    "Syntax error: expect token kind: {:?} found {:?} at position {}",
-                    expect_kind, token, self.position
-                );
+            return Err(ParseError::Invalid {
+                found: token,
+                reason: self.invalid.clone(),
+            });
+        }
+        if token.kind != expect_kind {
+            return Err(ParseError::Unexpected {
+                expected: expect_kind,
+                found: token,
+            });
+        }
+        self.current = None;
+        self.consumed = token.span;
+        Ok(token)
+    }
+
+    fn skip_blank(&mut self) {
+        // A `#!` first line lets scripts be run directly, read it as a comment.
+        if self.position == 0 && self.text.starts_with("#!") {
+            self.skip_line();
+        }
+        loop {
+            match Kind::classify(&self.current()) {
+                Kind::Space | Kind::EndLine => self.bump(),
+                Kind::Comment => self.skip_line(),
+                _ => break,
             }
-            return token;
-        } else {
-            panic!("Lexer error: expected {:?} found end of file", expect_kind)
         }
     }
-}
 
-impl Iterator for Tokenizer {
-    type Item = Token;
+    fn skip_line(&mut self) {
+        while self.current().is_some() && Kind::EndLine != Kind::classify(&self.current()) {
+            self.bump();
+        }
+    }
 
-    fn next(&mut self) -> Option<Token> {
-        let current = self.current();
-        let kind = Kind::classify(&current);
+    fn invalid(&mut self, value: String, reason: &str) -> Token {
+        self.invalid = String::from(reason);
+        Token::build(Kind::Invalid, value)
+    }
 
-        self.position += 1;
+    /// Reads a string literal, the opening quote is already consumed. Raw
+    /// strings keep backslashes as they are.
+    fn string(&mut self, raw: bool) -> Token {
+        let mut value = String::new();
+        let mut bad_escape = None;
+        loop {
+            match self.current() {
+                None => return self.invalid(String::from("\""), "unterminated string"),
+                Some('"') => {
+                    self.bump();
+                    break;
+                }
+                Some('\\') if !raw => {
+                    self.bump();
+                    match self.escape() {
+                        Ok(character) => value.push(character),
+                        Err(escape) => bad_escape = bad_escape.or(Some(escape)),
+                    }
+                }
+                Some(character) => {
+                    value.push(character);
+                    self.bump();
+                }
+            }
+        }
 
-        match kind {
-            Kind::Comment => {
-                while Kind::EndLine != Kind::classify(&self.current()) {
-                    self.position += 1;
+        match bad_escape {
+            Some(escape) => self.invalid(escape, "invalid escape"),
+            None => Token::build(Kind::Str, value),
+        }
+    }
+
+    /// Reads what follows a `\\` in a string, on error returns the escape as
+    /// it was written.
+    fn escape(&mut self) -> Result<char, String> {
+        let character = match self.current() {
+            Some(character) => character,
+            None => return Err(String::from("\\")),
+        };
+        self.bump();
+
+        match character {
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'u' => {
+                let mut written = String::from("\\u");
+                if self.current() != Some('{') {
+                    return Err(written);
                 }
+                written.push('{');
+                self.bump();
 
-                self.next()
-            }
-            Kind::Str => {
-                let c: Vec<char> = self.text[self.position..]
-                    .chars()
-                    .take_while(|b| Kind::classify(&Some(*b)) != Kind::Str)
-                    .collect();
-                self.position += c.len() + 1;
-                let w: String = c.clone().into_iter().collect();
-                Some(Token::build(kind, w))
-            }
-            Kind::List => {
-                let c: Vec<char> = self.text[self.position..]
-                    .chars()
-                    .skip(1) // Skip GroupBegin
-                    .take_while(|b| Kind::classify(&Some(*b)) != Kind::GroupEnd)
-                    .collect();
-                self.position += c.len() + 2;
-                let w: String = c.clone().into_iter().collect();
-                let ww: Vec<&str> = w.split(' ').collect();
-                Some(Token::build(kind, String::from(ww.join(","))))
-            }
-            Kind::Space | Kind::EndLine => self.next(),
-            Kind::Operator => {
-                if current == Some('/')
-                    && self.peek() == Some(Token::build(Kind::Comparison, String::from("=")))
-                {
-                    self.position += 1;
-                    Some(Token::build(Kind::Comparison, String::from("/=")))
-                } else {
-                    Some(Token::build(kind, format!("{}", current.unwrap())))
+                let mut digits = String::new();
+                while let Some(digit) = self.current().filter(|c| c.is_ascii_hexdigit()) {
+                    digits.push(digit);
+                    self.bump();
                 }
+                written.push_str(&digits);
+                if self.current() != Some('}') {
+                    return Err(written);
+                }
+                written.push('}');
+                self.bump();
+
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(::std::char::from_u32)
+                    .ok_or(written)
             }
-            Kind::GroupBegin | Kind::GroupEnd | Kind::Logical | Kind::Comparison => {
-                Some(Token::build(kind, format!("{}", current.unwrap())))
-            }
-            Kind::Alphanum => {
-                let mut chars = vec![current.unwrap()];
-                let mut next = self.current();
-                let mut kindnext = Kind::classify(&next);
-
-                while kindnext == kind || kindnext == Kind::Integer {
-                    chars.push(next.unwrap());
-                    self.position += 1;
-                    next = self.current();
-                    kindnext = Kind::classify(&next);
+            other => Err(format!("\\{}", other)),
+        }
+    }
+
+    fn digits(&mut self, number: &mut String) {
+        while let Some(digit) = self.current().filter(|c| c.is_ascii_digit()) {
+            number.push(digit);
+            self.bump();
+        }
+    }
+
+    /// Reads an integer or float literal like `-5`, `3.14` or `1.5e-3`, the
+    /// first char (a digit or sign) is already consumed.
+    fn number(&mut self, first: char) -> Token {
+        let mut number = first.to_string();
+        let mut kind = Kind::Integer;
+        self.digits(&mut number);
+
+        if self.current() == Some('.') && self.ahead(1).is_some_and(|c| c.is_ascii_digit()) {
+            kind = Kind::Float;
+            number.push('.');
+            self.bump();
+            self.digits(&mut number);
+        }
+
+        if let Some(exponent) = self.current().filter(|&c| c == 'e' || c == 'E') {
+            let signed = self.ahead(1) == Some('-') || self.ahead(1) == Some('+');
+            let digit = self.ahead(if signed { 2 } else { 1 });
+            if digit.is_some_and(|c| c.is_ascii_digit()) {
+                kind = Kind::Float;
+                number.push(exponent);
+                self.bump();
+                if signed {
+                    number.push(self.current().unwrap());
+                    self.bump();
                 }
+                self.digits(&mut number);
+            }
+        }
 
-                let word: String = chars.clone().into_iter().collect();
-                if let Some(reserved) = Kind::reserved(&word) {
-                    Some(Token {
-                        kind: reserved,
-                        value: word,
-                    })
-                } else {
-                    Some(Token {
-                        kind: Kind::ID,
-                        value: word,
-                    })
+        Token::build(kind, number)
+    }
+
+    /// Reads a symbol, the first char is already consumed. Operator chars
+    /// like `-`, `*` and `/` only stand alone when isolated, so `sum-list`,
+    /// `*debug*` and `str->int` are single names.
+    fn symbol(&mut self, first: char) -> Token {
+        let mut word = first.to_string();
+        while let Some(character) = self.current() {
+            match Kind::classify(&Some(character)) {
+                Kind::Alphanum | Kind::Integer | Kind::Operator => {
+                    word.push(character);
+                    self.bump();
                 }
+                _ => break,
             }
-            _ => {
-                if current == None {
-                    Some(Token::build(Kind::EOF, String::new()))
-                } else {
-                    let mut chars = vec![current.unwrap()];
+        }
+
+        match Kind::reserved(&word) {
+            Some(reserved) => Token::build(reserved, word),
+            None => Token::build(Kind::ID, word),
+        }
+    }
+
+    fn scan(&mut self) -> Option<Token> {
+        let current = self.current();
+        let kind = Kind::classify(&current);
+
+        self.bump();
+
+        match kind {
+            Kind::Str => Some(self.string(false)),
+            Kind::Integer => Some(self.number(current.unwrap())),
+            Kind::Operator
+                if (current == Some('-') || current == Some('+'))
+                    && Kind::classify(&self.current()) == Kind::Integer =>
+            {
+                Some(self.number(current.unwrap()))
+            }
+            Kind::Unquote if self.current() == Some('@') => {
+                self.bump();
+                Some(Token::build(Kind::UnquoteSplice, String::from("~@")))
+            }
+            Kind::List
+            | Kind::Quasiquote
+            | Kind::Unquote
+            | Kind::GroupBegin
+            | Kind::GroupEnd
+            | Kind::ArgsBegin
+            | Kind::ArgsEnd
+            | Kind::Logical
+            | Kind::Comparison => Some(Token::build(kind, current.unwrap().to_string())),
+            Kind::Alphanum if current == Some('#') && self.current() == Some('"') => {
+                self.bump();
+                Some(self.string(true))
+            }
+            Kind::Operator | Kind::Alphanum => Some(self.symbol(current.unwrap())),
+            _ => match current {
+                None => Some(Token::build(Kind::EOF, String::new())),
+                Some(character) => {
+                    let mut chars = vec![character];
                     let mut next = self.current();
                     let mut kindnext = Kind::classify(&next);
 
                     while kindnext == kind {
                         chars.push(next.unwrap());
-                        self.position += 1;
+                        self.bump();
                         next = self.current();
                         kindnext = Kind::classify(&next);
                     }
 
                     Some(Token::build(kind, chars.into_iter().collect()))
                 }
-            }
+            },
         }
     }
 }
 
+impl Iterator for Tokenizer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        self.skip_blank();
+        let start = self.location();
+        let token = self.scan();
+        let span = Span::new(start, self.location());
+        token.map(|token| token.at(span))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,42 +633,27 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Operator,
-                value: String::from("+"),
-            }
+            Token::build(Kind::Operator, String::from("+"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
     }
 
@@ -429,10 +664,7 @@ mod tests {
         tokenizer.advance();
 
         assert_eq!(
-            Some(Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }),
+            Some(Token::build(Kind::GroupBegin, String::from("("))),
             tokenizer.get()
         )
     }
@@ -443,13 +675,53 @@ mod tests {
         let mut tokenizer = Tokenizer::new(String::from(text));
 
         assert_eq!(
-            Some(Token {
-                kind: Kind::Operator,
-                value: String::from("+"),
-            }),
+            Some(Token::build(Kind::GroupBegin, String::from("("))),
+            tokenizer.peek()
+        );
+        assert_eq!(
+            Some(Token::build(Kind::GroupBegin, String::from("("))),
+            tokenizer.peek()
+        );
+        assert_eq!(
+            Token::build(Kind::GroupBegin, String::from("(")),
+            tokenizer.consume(Kind::GroupBegin).unwrap()
+        );
+        assert_eq!(
+            Some(Token::build(Kind::Operator, String::from("+"))),
             tokenizer.peek()
         );
-        assert_eq!(Some('('), tokenizer.current())
+    }
+
+    #[test]
+    fn test_tokenizer_position_is_byte_offset() {
+        let text = "(print \"olá\")\n(é)";
+        let mut tokenizer = Tokenizer::new(String::from(text));
+        let spans: Vec<Span> = tokenizer.by_ref().take(7).map(|t| t.span).collect();
+
+        assert_eq!(
+            Span::new(
+                Position {
+                    offset: 7,
+                    line: 1,
+                    column: 8,
+                },
+                Position {
+                    offset: 13,
+                    line: 1,
+                    column: 13,
+                },
+            ),
+            spans[2]
+        );
+        assert_eq!(
+            Position {
+                offset: 16,
+                line: 2,
+                column: 2,
+            },
+            spans[5].start
+        );
+        assert_eq!(text.len(), tokenizer.position);
     }
 
     #[test]
@@ -458,11 +730,8 @@ mod tests {
         let mut tokenizer = Tokenizer::new(String::from(text));
 
         assert_eq!(
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            },
-            tokenizer.advance().consume(Kind::GroupBegin)
+            Token::build(Kind::GroupBegin, String::from("(")),
+            tokenizer.advance().consume(Kind::GroupBegin).unwrap()
         );
     }
 
@@ -472,39 +741,24 @@ mod tests {
         let mut tokenizer = Tokenizer::new(String::from(text));
 
         assert_eq!(
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            },
-            tokenizer.advance().consume(Kind::GroupBegin)
+            Token::build(Kind::GroupBegin, String::from("(")),
+            tokenizer.advance().consume(Kind::GroupBegin).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Comparison,
-                value: String::from("="),
-            },
-            tokenizer.advance().consume(Kind::Comparison)
+            Token::build(Kind::Comparison, String::from("=")),
+            tokenizer.advance().consume(Kind::Comparison).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            },
-            tokenizer.advance().consume(Kind::Integer)
+            Token::build(Kind::Integer, String::from("1")),
+            tokenizer.advance().consume(Kind::Integer).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            },
-            tokenizer.advance().consume(Kind::Integer)
+            Token::build(Kind::Integer, String::from("1")),
+            tokenizer.advance().consume(Kind::Integer).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            },
-            tokenizer.advance().consume(Kind::GroupEnd)
+            Token::build(Kind::GroupEnd, String::from(")")),
+            tokenizer.advance().consume(Kind::GroupEnd).unwrap()
         );
     }
 
@@ -514,39 +768,24 @@ mod tests {
         let mut tokenizer = Tokenizer::new(String::from(text));
 
         assert_eq!(
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            },
-            tokenizer.advance().consume(Kind::GroupBegin)
+            Token::build(Kind::GroupBegin, String::from("(")),
+            tokenizer.advance().consume(Kind::GroupBegin).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Comparison,
-                value: String::from("/="),
-            },
-            tokenizer.advance().consume(Kind::Comparison)
+            Token::build(Kind::Comparison, String::from("/=")),
+            tokenizer.advance().consume(Kind::Comparison).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            },
-            tokenizer.advance().consume(Kind::Integer)
+            Token::build(Kind::Integer, String::from("1")),
+            tokenizer.advance().consume(Kind::Integer).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            },
-            tokenizer.advance().consume(Kind::Integer)
+            Token::build(Kind::Integer, String::from("1")),
+            tokenizer.advance().consume(Kind::Integer).unwrap()
         );
         assert_eq!(
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            },
-            tokenizer.advance().consume(Kind::GroupEnd)
+            Token::build(Kind::GroupEnd, String::from(")")),
+            tokenizer.advance().consume(Kind::GroupEnd).unwrap()
         );
     }
 
@@ -557,42 +796,27 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Operator,
-                value: String::from("+"),
-            }
+            Token::build(Kind::Operator, String::from("+"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
     }
 
@@ -603,64 +827,40 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Operator,
-                value: String::from("+"),
-            }
+            Token::build(Kind::Operator, String::from("+"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("1"),
-            }
+            Token::build(Kind::Integer, String::from("1"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         )
     }
 
@@ -671,31 +871,19 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Str,
-                value: String::from("ola"),
-            }
+            Token::build(Kind::Str, String::from("ola"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
     }
 
@@ -705,31 +893,39 @@ mod tests {
         let mut tokenizer = Tokenizer::new(String::from(text));
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::List,
-                value: String::from("1,2,true"),
-            }
+            Token::build(Kind::List, String::from("'"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
+        );
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::build(Kind::Integer, String::from("1"))
+        );
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::build(Kind::Integer, String::from("2"))
+        );
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::build(Kind::Bolean, String::from("true"))
+        );
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::build(Kind::GroupEnd, String::from(")"))
+        );
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
     }
 
@@ -740,117 +936,69 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::If,
-                value: String::from("if"),
-            }
+            Token::build(Kind::If, String::from("if"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Comparison,
-                value: String::from("="),
-            }
+            Token::build(Kind::Comparison, String::from("="))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }
+            Token::build(Kind::Integer, String::from("9"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("9"),
-            }
+            Token::build(Kind::Integer, String::from("9"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Str,
-                value: String::from("eq"),
-            }
+            Token::build(Kind::Str, String::from("eq"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Str,
-                value: String::from("neq"),
-            }
+            Token::build(Kind::Str, String::from("neq"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         )
     }
 
@@ -861,87 +1009,51 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::When,
-                value: String::from("when"),
-            }
+            Token::build(Kind::When, String::from("when"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Comparison,
-                value: String::from(">"),
-            }
+            Token::build(Kind::Comparison, String::from(">"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("3"),
-            }
+            Token::build(Kind::Integer, String::from("3"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("2"),
-            }
+            Token::build(Kind::Integer, String::from("2"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::StdOut,
-                value: String::from("print"),
-            }
+            Token::build(Kind::StdOut, String::from("print"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Str,
-                value: String::from("big"),
-            }
+            Token::build(Kind::Str, String::from("big"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
     }
 
@@ -952,42 +1064,27 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::VarDefine,
-                value: String::from("def"),
-            }
+            Token::build(Kind::VarDefine, String::from("def"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ID,
-                value: String::from("x"),
-            }
+            Token::build(Kind::ID, String::from("x"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("2"),
-            }
+            Token::build(Kind::Integer, String::from("2"))
         );
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         )
     }
 
@@ -998,87 +1095,240 @@ mod tests {
 
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::FnDefine,
-                value: String::from("defn"),
-            }
+            Token::build(Kind::FnDefine, String::from("defn"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ID,
-                value: String::from("maior_que_dois"),
-            }
+            Token::build(Kind::ID, String::from("maior_que_dois"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ArgsBegin,
-                value: String::from("["),
-            }
+            Token::build(Kind::ArgsBegin, String::from("["))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ID,
-                value: String::from("arg"),
-            }
+            Token::build(Kind::ID, String::from("arg"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ArgsEnd,
-                value: String::from("]"),
-            }
+            Token::build(Kind::ArgsEnd, String::from("]"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupBegin,
-                value: String::from("("),
-            }
+            Token::build(Kind::GroupBegin, String::from("("))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Comparison,
-                value: String::from(">"),
-            }
+            Token::build(Kind::Comparison, String::from(">"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::ID,
-                value: String::from("arg"),
-            }
+            Token::build(Kind::ID, String::from("arg"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::Integer,
-                value: String::from("2"),
-            }
+            Token::build(Kind::Integer, String::from("2"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
         );
         assert_eq!(
             tokenizer.next().unwrap(),
-            Token {
-                kind: Kind::GroupEnd,
-                value: String::from(")"),
-            }
+            Token::build(Kind::GroupEnd, String::from(")"))
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_with_span() {
+        let text = "(+ 1\n   22)";
+        let mut tokenizer = Tokenizer::new(String::from(text));
+        let spans: Vec<Span> = tokenizer.by_ref().take(4).map(|t| t.span).collect();
+
+        assert_eq!(
+            Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            spans[0].start
+        );
+        assert_eq!(
+            Span::new(
+                Position {
+                    offset: 8,
+                    line: 2,
+                    column: 4,
+                },
+                Position {
+                    offset: 10,
+                    line: 2,
+                    column: 6,
+                },
+            ),
+            spans[3]
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_nested_args() {
+        let text = "[[a]]";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let kinds: Vec<Kind> = tokenizer.take(5).map(|t| t.kind).collect();
+
+        assert_eq!(
+            vec![
+                Kind::ArgsBegin,
+                Kind::ArgsBegin,
+                Kind::ID,
+                Kind::ArgsEnd,
+                Kind::ArgsEnd,
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_numbers() {
+        let text = "(- 5 -3 +2 0.25 -1.5e-3 2E10)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let tokens: Vec<Token> = tokenizer.take(9).collect();
+
+        assert_eq!(
+            vec![
+                Token::build(Kind::GroupBegin, String::from("(")),
+                Token::build(Kind::Operator, String::from("-")),
+                Token::build(Kind::Integer, String::from("5")),
+                Token::build(Kind::Integer, String::from("-3")),
+                Token::build(Kind::Integer, String::from("+2")),
+                Token::build(Kind::Float, String::from("0.25")),
+                Token::build(Kind::Float, String::from("-1.5e-3")),
+                Token::build(Kind::Float, String::from("2E10")),
+                Token::build(Kind::GroupEnd, String::from(")")),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_symbols() {
+        let text = "(sum-list *debug* str->int empty? - a/b)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let tokens: Vec<Token> = tokenizer.take(8).collect();
+
+        assert_eq!(
+            vec![
+                Token::build(Kind::GroupBegin, String::from("(")),
+                Token::build(Kind::ID, String::from("sum-list")),
+                Token::build(Kind::ID, String::from("*debug*")),
+                Token::build(Kind::ID, String::from("str->int")),
+                Token::build(Kind::ID, String::from("empty?")),
+                Token::build(Kind::Operator, String::from("-")),
+                Token::build(Kind::ID, String::from("a/b")),
+                Token::build(Kind::GroupEnd, String::from(")")),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_quasiquote() {
+        let text = "`(a ~b ~@c)";
+        let tokenizer = Tokenizer::new(String::from(text));
+        let tokens: Vec<Token> = tokenizer.take(8).collect();
+
+        assert_eq!(
+            vec![
+                Token::build(Kind::Quasiquote, String::from("`")),
+                Token::build(Kind::GroupBegin, String::from("(")),
+                Token::build(Kind::ID, String::from("a")),
+                Token::build(Kind::Unquote, String::from("~")),
+                Token::build(Kind::ID, String::from("b")),
+                Token::build(Kind::UnquoteSplice, String::from("~@")),
+                Token::build(Kind::ID, String::from("c")),
+                Token::build(Kind::GroupEnd, String::from(")")),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_skips_shebang() {
+        let text = "#!/usr/bin/env moeda\n\t(print 1)";
+        let mut tokenizer = Tokenizer::new(String::from(text));
+        let token = tokenizer.next().unwrap();
+
+        assert_eq!(Token::build(Kind::GroupBegin, String::from("(")), token);
+        assert_eq!(2, token.span.start.line);
+        assert_eq!(2, token.span.start.column);
+    }
+
+    #[test]
+    fn test_tokenizer_next_str_with_escapes() {
+        let text = r#""say \"hi\"\n\tback\\slash \u{e9}""#;
+        let mut tokenizer = Tokenizer::new(String::from(text));
+
+        assert_eq!(
+            Token::build(Kind::Str, String::from("say \"hi\"\n\tback\\slash é")),
+            tokenizer.next().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_next_raw_str() {
+        let text = r##"#"\d+\.\w" 1"##;
+        let mut tokenizer = Tokenizer::new(String::from(text));
+
+        assert_eq!(
+            Token::build(Kind::Str, String::from(r"\d+\.\w")),
+            tokenizer.next().unwrap()
+        );
+        assert_eq!(
+            Token::build(Kind::Integer, String::from("1")),
+            tokenizer.next().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tokenizer_consume_unterminated_str() {
+        let text = "(print \"ola)";
+        let mut tokenizer = Tokenizer::new(String::from(text));
+        tokenizer.advance().consume(Kind::GroupBegin).unwrap();
+        tokenizer.advance().consume(Kind::StdOut).unwrap();
+
+        let error = tokenizer.advance().consume(Kind::Str).unwrap_err();
+        assert_eq!(
+            ParseError::Invalid {
+                found: Token::build(Kind::Invalid, String::from("\"")),
+                reason: String::from("unterminated string"),
+            },
+            error
+        );
+        assert_eq!(8, error.span().start.column);
+    }
+
+    #[test]
+    fn test_tokenizer_consume_invalid_escape() {
+        let text = r#""a\qb" "\u{110000}""#;
+        let mut tokenizer = Tokenizer::new(String::from(text));
+
+        assert_eq!(
+            ParseError::Invalid {
+                found: Token::build(Kind::Invalid, String::from(r"\q")),
+                reason: String::from("invalid escape"),
+            },
+            tokenizer.advance().consume(Kind::Str).unwrap_err()
+        );
+        assert_eq!(
+            ParseError::Invalid {
+                found: Token::build(Kind::Invalid, String::from(r"\u{110000}")),
+                reason: String::from("invalid escape"),
+            },
+            tokenizer.advance().consume(Kind::Str).unwrap_err()
         );
     }
 }
@@ -1,6 +1,9 @@
 extern crate moeda;
 
 use moeda::repl::Repl;
+use std::env;
+use std::fs;
+use std::process::Command;
 
 
 #[cfg(test)]
@@ -49,6 +52,74 @@ mod operations {
         let mut repl = Repl::new();
         assert_eq!(format!("3"), repl.eval(source_code))
     }
+    #[test]
+    fn repl_eval_negative() {
+        let source_code = String::from("(+ -5 2)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("-3"), repl.eval(source_code))
+    }
+    #[test]
+    fn repl_eval_float() {
+        let source_code = String::from("(* 1.5 2.5)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("3.75"), repl.eval(source_code))
+    }
+    #[test]
+    fn repl_eval_float_promotion() {
+        let source_code = String::from("(+ 1 0.5 -2)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("-0.5"), repl.eval(source_code))
+    }
+    #[test]
+    fn repl_eval_float_whole() {
+        let source_code = String::from("(/ 5 2.5)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("2.0"), repl.eval(source_code))
+    }
+    #[test]
+    fn repl_eval_float_exponent() {
+        let source_code = String::from("(+ 1e3 2.5E-1)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("1000.25"), repl.eval(source_code))
+    }
+    #[test]
+    fn repl_eval_float_overflow() {
+        let source_code = String::from("1.5e400");
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: invalid float `1.5e400` at line 1, column 1"),
+            repl.eval(source_code)
+        )
+    }
+    #[test]
+    fn repl_eval_operation_errors() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Operation error: can't add \"a\" and 1 at line 1, column 1"),
+            repl.eval(String::from("(+ \"a\" 1)"))
+        );
+        assert_eq!(
+            format!("Operation error: can't divide 1 by zero at line 1, column 1"),
+            repl.eval(String::from("(/ 1 0)"))
+        );
+        assert_eq!(
+            format!("Operation error: can't take the remainder of 7 by zero at line 1, column 1"),
+            repl.eval(String::from("(rem 7 0)"))
+        );
+        assert_eq!(
+            format!("Operation error: - expects at least one argument at line 1, column 1"),
+            repl.eval(String::from("(-)"))
+        );
+        assert_eq!(
+            format!("Operation error: inc expects one argument at line 1, column 1"),
+            repl.eval(String::from("(inc)"))
+        );
+        assert_eq!(
+            format!("Operation error: max expects at least one argument at line 1, column 1"),
+            repl.eval(String::from("(max)"))
+        );
+        assert_eq!(format!("0"), repl.eval(String::from("(+)")));
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +139,12 @@ mod comparison {
         assert_eq!(format!("false"), repl.eval(source_code))
     }
     #[test]
+    fn repl_eval_lt_float() {
+        let source_code = String::from("(< -1 -0.5 0 2.5)");
+        let mut repl = Repl::new();
+        assert_eq!(format!("true"), repl.eval(source_code))
+    }
+    #[test]
     fn repl_eval_gt() {
         let source_code = String::from("(> 2 1)");
         let mut repl = Repl::new();
@@ -103,6 +180,21 @@ mod comparison {
         let mut repl = Repl::new();
         assert_eq!(format!("1"), repl.eval(source_code))
     }
+    #[test]
+    fn repl_eval_nan() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("false"), repl.eval(String::from("(< (/ 0.0 0.0) 1)")));
+        assert_eq!(format!("NaN"), repl.eval(String::from("(max 1.0 (/ 0.0 0.0))")));
+    }
+    #[test]
+    fn repl_eval_compare_invalid() {
+        let source_code = String::from("(< 1 \"a\")");
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Operation error: can't compare 1 with \"a\" at line 1, column 1"),
+            repl.eval(source_code)
+        )
+    }
 }
 
 #[cfg(test)]
@@ -148,153 +240,1244 @@ mod logical {
 }
 
 #[cfg(test)]
-mod conditional {
+mod destructuring {
     use super::*;
 
     #[test]
-    fn repl_eval_if() {
+    fn repl_eval_let_destructure() {
         let mut repl = Repl::new();
-        assert_eq!(format!("1"), repl.eval(String::from("(if true (1) (0))")));
-        assert_eq!(format!("1"), repl.eval(String::from("(if true 1 0)")));
-        assert_eq!(format!("0"), repl.eval(String::from("(if false (1) (0))")));
-        assert_eq!(format!("0"), repl.eval(String::from("(if false 1 0)")));
+        assert_eq!(format!("3"), repl.eval(String::from("(let [[a b] (list 1 2)] (+ a b))")));
+        assert_eq!(
+            format!("(1 (2 3))"),
+            repl.eval(String::from("(let [[head & tail] (list 1 2 3)] (list head tail))"))
+        );
     }
 
     #[test]
-    fn repl_eval_when() {
+    fn repl_eval_nested_destructure() {
         let mut repl = Repl::new();
-        assert_eq!(format!("199"), repl.eval(String::from("(when true (199))")));
-        assert_eq!(format!(""), repl.eval(String::from("(when false (0))")));
+        assert_eq!(
+            format!("(1 2 3)"),
+            repl.eval(String::from("(let [[[a b] & [c]] (list (list 1 2) 3)] (list a b c))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_defn_destructure() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn second [[_ b & more]] b)"));
+        assert_eq!(format!("2"), repl.eval(String::from("(second (list 1 2 3))")));
+    }
+
+    #[test]
+    fn repl_eval_fn_destructure() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("2"), repl.eval(String::from("((fn [[a b]] b) (list 1 2))")));
+    }
+
+    #[test]
+    fn repl_eval_loop_destructure() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("6"),
+            repl.eval(String::from("(loop [[x & xs] (list 1 2 3) acc 0] (if (empty? xs) (+ acc x) (recur xs (+ acc x))))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_destructure_wrong_length() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Value error: [a b] expects 2 items, got 3 at line 1, column 7"),
+            repl.eval(String::from("(let [[a b] (list 1 2 3)] a)"))
+        );
+        assert_eq!(
+            format!("Value error: [a b & c] expects at least 2 items, got 1 at line 1, column 7"),
+            repl.eval(String::from("((fn [[a b & c]] a) (list 1))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_destructure_not_a_list() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Value error: [a] expects a list, got 1 at line 1, column 7"),
+            repl.eval(String::from("(let [[a] 1] a)"))
+        );
     }
 }
 
 #[cfg(test)]
-mod indentifier {
+mod pattern_matching {
     use super::*;
 
     #[test]
-    fn repl_eval_variables() {
+    fn repl_eval_match_literals() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(def x \"moeda\")")));
+        repl.eval(String::from("(defn describe [x] (match x 0 \"zero\" \"oi\" \"greeting\" true \"yes\" nil \"nothing\" _ \"other\"))"));
+        assert_eq!(format!("zero"), repl.eval(String::from("(describe 0)")));
+        assert_eq!(format!("greeting"), repl.eval(String::from("(describe \"oi\")")));
+        assert_eq!(format!("yes"), repl.eval(String::from("(describe true)")));
+        assert_eq!(format!("nothing"), repl.eval(String::from("(describe (first (list)))")));
+        assert_eq!(format!("other"), repl.eval(String::from("(describe 7)")));
+    }
+
+    #[test]
+    fn repl_eval_match_lists() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn sum [xs] (match xs [] 0 [head & tail] (+ head (sum tail))))"));
+        assert_eq!(format!("6"), repl.eval(String::from("(sum (list 1 2 3))")));
         assert_eq!(
-            format!("eq"),
-            repl.eval(String::from("(if (/= x \"moeda\") (\"neq\") (\"eq\"))"))
-        )
+            format!("(2 1)"),
+            repl.eval(String::from("(match (list 1 2) [a] a [a b] (list b a))"))
+        );
+        assert_eq!(
+            format!("3"),
+            repl.eval(String::from("(match (list 1 (list 2 3)) [1 [_ c]] c _ 0)"))
+        );
     }
 
     #[test]
-    fn repl_eval_variables_already_defined() {
+    fn repl_eval_match_ampersand_string() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(def x \"moeda\")")));
+        assert_eq!(format!("2"), repl.eval(String::from("(match (list 1) [\"&\" x] 1 _ 2)")));
         assert_eq!(
-            format!("Value error: variable x has already defined."),
-            repl.eval(String::from("(def x \"rust\")"))
+            format!("2"),
+            repl.eval(String::from("(match (list \"&\" 2) [\"&\" x] x _ 0)"))
         );
     }
 
     #[test]
-    fn repl_eval_variables_not_defined() {
+    fn repl_eval_match_guard() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn sign [n] (match n 0 0 x :when (< x 0) -1 _ 1))"));
+        assert_eq!(format!("-1"), repl.eval(String::from("(sign -5)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(sign 5)")));
+    }
+
+    #[test]
+    fn repl_eval_match_in_tail_position() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn total [xs acc] (match xs [] acc [x & more] (total more (+ acc x))))"));
+        assert_eq!(format!("4950"), repl.eval(String::from("(total (range 100) 0)")));
+    }
+
+    #[test]
+    fn repl_eval_match_no_clause() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(def y \"moeda\")")));
         assert_eq!(
-            format!("Variable x doesn't exist in this context"),
-            repl.eval(String::from("(print x)"))
+            format!("Value error: no match clause for 3 at line 1, column 1"),
+            repl.eval(String::from("(match 3 1 \"one\" 2 \"two\")"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_match_unreachable_warning() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("1"), repl.eval(String::from("(match 1 x x 2 0)")));
+        assert_eq!(
+            vec![format!("Warning: unreachable match clause `2` at line 1, column 14")],
+            repl.warnings().iter().map(|warning| warning.to_string()).collect::<Vec<String>>()
         );
     }
 }
 
 #[cfg(test)]
-mod functions {
+mod quoting {
     use super::*;
 
     #[test]
-    fn repl_eval_defn() {
+    fn repl_eval_quote_symbol() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(defn f [] (true))")));
-        assert_eq!(format!("true"), repl.eval(String::from("(f)")));
+        assert_eq!(format!("x"), repl.eval(String::from("'x")));
+        assert_eq!(format!("x"), repl.eval(String::from("(quote x)")));
     }
 
     #[test]
-    fn repl_eval_defn_with_args() {
+    fn repl_eval_quote_nested_form() {
         let mut repl = Repl::new();
         assert_eq!(
-            format!(""),
-            repl.eval(String::from("(defn f [n t] (* n t))"))
+            format!("(defn f [x] (+ x 1))"),
+            repl.eval(String::from("(quote (defn f [x] (+ x 1)))"))
         );
-        assert_eq!(format!("8"), repl.eval(String::from("(f 2 4)")));
+        assert_eq!(format!("3"), repl.eval(String::from("(count '(a (b c) 'd))")));
     }
 
     #[test]
-    fn repl_eval_defn_already_defined() {
+    fn repl_eval_quasiquote() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(defn f [] (true))")));
+        repl.eval(String::from("(def x 1)"));
+        repl.eval(String::from("(def xs (list 2 3))"));
+        assert_eq!(format!("(a 1 xs)"), repl.eval(String::from("`(a ~x xs)")));
+        assert_eq!(format!("(a 1 2 3 [4 2 3])"), repl.eval(String::from("`(a ~x ~@xs [4 ~@xs])")));
+        assert_eq!(format!("(+ 1 (* 2 3))"), repl.eval(String::from("`(+ ~x (* ~@xs))")));
+    }
+
+    #[test]
+    fn repl_eval_spelled_out_quasiquote() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(def x 1)"));
+        assert_eq!(format!("(1 2)"), repl.eval(String::from("(eval (quote (quasiquote (1 2))))")));
         assert_eq!(
-            format!("Value error: variable f has already defined."),
-            repl.eval(String::from("(defn f [] (false))"))
+            format!("(a 1 2 3)"),
+            repl.eval(String::from("(quasiquote (a (unquote x) (unquote-splicing (list 2 3))))"))
         );
     }
 
     #[test]
-    fn repl_eval_defn_call_function() {
+    fn repl_eval_macro_returning_quasiquote() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(defn f [n] (* n n))")));
-        assert_eq!(format!("4"), repl.eval(String::from("(f 2)")));
+        repl.eval(String::from("(defmacro later [x] (list 'quasiquote (list 'a (list 'unquote x))))"));
+        assert_eq!(format!("(a 3)"), repl.eval(String::from("(later (+ 1 2))")));
     }
 
     #[test]
-    fn repl_eval_defn_call_function_with_more_args() {
+    fn repl_eval_splice_not_a_list() {
         let mut repl = Repl::new();
         assert_eq!(
-            format!(""),
-            repl.eval(String::from("(defn f [n t] (+ (* n n) t))"))
+            format!("Value error: ~@ expects a list, got 1 at line 1, column 7"),
+            repl.eval(String::from("`(a ~@1)"))
         );
-        assert_eq!(format!("9"), repl.eval(String::from("(f 2 5)")));
     }
 
     #[test]
-    fn repl_eval_defn_call_function_not_defined() {
+    fn repl_eval_macro_with_template() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(defn f [n] (* n n))")));
+        repl.eval(String::from("(defmacro unless [c & body] `(if ~c nil (do ~@body)))"));
+        assert_eq!(format!("2"), repl.eval(String::from("(unless false 1 2)")));
         assert_eq!(
-            format!("Value error: g is not callable"),
-            repl.eval(String::from("(g 2)"))
+            format!("(if (> a 1) nil (do (print a) a))"),
+            repl.eval(String::from("(macroexpand '(unless (> a 1) (print a) a))"))
         );
     }
 }
 
 #[cfg(test)]
-mod stdout {
+mod macros {
     use super::*;
 
     #[test]
-    fn repl_eval_print() {
+    fn repl_eval_defmacro() {
         let mut repl = Repl::new();
-        assert_eq!(format!(""), repl.eval(String::from("(print true)")));
+        assert_eq!(format!(""), repl.eval(String::from("(defmacro unless [c a b] (list 'if c b a))")));
+        assert_eq!(format!("1"), repl.eval(String::from("(unless false 1 2)")));
+        assert_eq!(format!("2"), repl.eval(String::from("(unless true 1 2)")));
+    }
+
+    #[test]
+    fn repl_eval_macro_args_are_not_evaluated() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro ignore [form] 0)"));
+        assert_eq!(format!("0"), repl.eval(String::from("(ignore (undefined [x] y))")));
+    }
+
+    #[test]
+    fn repl_eval_macro_in_same_source() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("3"),
+            repl.eval(String::from("(defmacro const [name value] (list 'def name value)) (const three 3) three"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_macro_using_earlier_definitions() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("1"),
+            repl.eval(String::from("(defn helper [x] x) (defmacro m [x] (helper x)) (m 1)"))
+        );
+        assert_eq!(format!("3"), repl.eval(String::from("(def n 3) (defmacro k [] n) (k)")));
+    }
+
+    #[test]
+    fn repl_eval_macro_defined_when_run() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(if false (defmacro never [] 1) nil)"));
+        assert_eq!(
+            format!("Value error: never is not callable at line 1, column 2"),
+            repl.eval(String::from("(never)"))
+        );
+        assert_eq!(
+            format!("5"),
+            repl.eval(String::from("(let [x 5] (defmacro five [] x)) (five)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_local_name_hides_macro() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("7"),
+            repl.eval(String::from("(defmacro m [] 1) (defn g [m] (m)) (g (fn [] 7))"))
+        );
+        assert_eq!(format!("1"), repl.eval(String::from("(let [x (m)] x)")));
+    }
+
+    #[test]
+    fn repl_eval_macro_not_run_leaves_name_free() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("2"),
+            repl.eval(String::from("(when false (defmacro m [] 1)) (defn m [] 2) (m)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_macro_building_a_function() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro defthunk [name body] (list 'defn name (vector) body))"));
+        repl.eval(String::from("(defthunk answer (* 6 7))"));
+        assert_eq!(format!("42"), repl.eval(String::from("(answer)")));
+    }
+
+    #[test]
+    fn repl_eval_macro_calling_macro() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro unless [c a b] (list 'if c b a))"));
+        repl.eval(String::from("(defmacro when-not [c a] (list 'unless c a nil))"));
+        assert_eq!(format!("1"), repl.eval(String::from("(when-not false 1)")));
+    }
+
+    #[test]
+    fn repl_eval_macro_expanding_to_recur() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro again [x] (list 'recur x))"));
+        assert_eq!(
+            format!("3"),
+            repl.eval(String::from("(loop [i 0] (if (< i 3) (again (inc i)) i))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_macroexpand() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro unless [c a b] (list 'if c b a))"));
+        assert_eq!(
+            format!("(if (> x 1) no yes)"),
+            repl.eval(String::from("(macroexpand '(unless (> x 1) yes no))"))
+        );
+        assert_eq!(format!("(f 1)"), repl.eval(String::from("(macroexpand '(f 1))")));
+    }
+
+    #[test]
+    fn repl_eval_macro_arity_error() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro unless [c a b] (list 'if c b a))"));
+        assert_eq!(
+            format!("Value error: unless expects 3 arguments, got 1 at line 1, column 1"),
+            repl.eval(String::from("(unless true)"))
+        );
     }
-}
 
+    #[test]
+    fn repl_eval_macro_invalid_code() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro bad [] (list 1 2))"));
+        assert_eq!(
+            format!("Macro error: bad returned invalid code `(1 2)` at line 1, column 1"),
+            repl.eval(String::from("(bad)"))
+        );
+    }
+}
 
 #[cfg(test)]
-mod programs {
+mod eval {
     use super::*;
 
     #[test]
-    fn repl_multiple4_program() {
+    fn repl_eval_read_string() {
         let mut repl = Repl::new();
-        let mut source_code = r#"(defn mult4 [x] (* x 4))"#;
-        repl.eval(String::from(source_code));
-        source_code = "(defn resultado [y] (mult4 y)";
-        repl.eval(String::from(source_code));
-        source_code = "(resultado 5)";
-        assert_eq!(format!("20"), repl.eval(String::from(source_code)));
+        assert_eq!(format!("(+ 1 [2 x])"), repl.eval(String::from("(read-string \"(+ 1 [2 x])\")")));
+        assert_eq!(format!("3"), repl.eval(String::from("(eval (read-string \"(+ 1 2)\"))")));
     }
 
     #[test]
-    fn repl_fibonacci_program() {
+    fn repl_eval_read_quasiquote() {
         let mut repl = Repl::new();
-        let mut source_code = "(defn fib [n] (if (or (= n 1) (= n 2)) 1 (+ (fib (- n 1)) (fib (- n 2)))))";
-        assert_eq!(format!(""), repl.eval(String::from(source_code)));
-        source_code = "(fib 7)";
-        assert_eq!(format!("13"), repl.eval(String::from(source_code)));
+        repl.eval(String::from("(def x 3)"));
+        assert_eq!(format!("(1 3)"), repl.eval(String::from("(eval (read-string \"`(1 ~x)\"))")));
+    }
+
+    #[test]
+    fn repl_eval_built_form() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("6"), repl.eval(String::from("(eval (list '* 2 3))")));
+        assert_eq!(format!("5"), repl.eval(String::from("(eval '(def x 5)) x")));
+    }
+
+    #[test]
+    fn repl_eval_in_current_frame() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn run [a code] (eval code))"));
+        assert_eq!(format!("6"), repl.eval(String::from("(run 2 '(* a 3))")));
+    }
+
+    #[test]
+    fn repl_eval_expands_macros() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defmacro unless [c a b] (list 'if c b a))"));
+        assert_eq!(format!("2"), repl.eval(String::from("(eval (read-string \"(unless true 1 2)\"))")));
+    }
+
+    #[test]
+    fn repl_eval_errors() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Value error: read-string got invalid code `(+ 1` at line 1, column 1"),
+            repl.eval(String::from("(read-string \"(+ 1\")"))
+        );
+        assert_eq!(
+            format!("Syntax error: unexpected token `)` at line 1, column 1"),
+            repl.eval(String::from("(eval '(if))"))
+        );
+        assert_eq!(
+            format!("Value error: eval expects a single form at line 1, column 1"),
+            repl.eval(String::from("(eval 1 2)"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod conditional {
+    use super::*;
+
+    #[test]
+    fn repl_eval_if() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("1"), repl.eval(String::from("(if true (1) (0))")));
+        assert_eq!(format!("1"), repl.eval(String::from("(if true 1 0)")));
+        assert_eq!(format!("0"), repl.eval(String::from("(if false (1) (0))")));
+        assert_eq!(format!("0"), repl.eval(String::from("(if false 1 0)")));
+    }
+
+    #[test]
+    fn repl_eval_if_without_else() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("1"), repl.eval(String::from("(if true 1)")));
+        assert_eq!(format!(""), repl.eval(String::from("(if false 1)")));
+        assert_eq!(format!("true"), repl.eval(String::from("(= (if false 1) (if false 2))")));
+    }
+
+    #[test]
+    fn repl_eval_if_any_value() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("1"), repl.eval(String::from("(if (fn [] 1) 1 2)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(if first 1 2)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(if 'a 1 2)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(if \"\" 1 2)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(when (list) 1)")));
+    }
+
+    #[test]
+    fn repl_eval_cond() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn sign [n] (cond (< n 0) \"negative\" (= n 0) \"zero\" :else \"positive\"))",
+        ));
+        assert_eq!(format!("negative"), repl.eval(String::from("(sign -3)")));
+        assert_eq!(format!("zero"), repl.eval(String::from("(sign 0)")));
+        assert_eq!(format!("positive"), repl.eval(String::from("(sign 2)")));
+    }
+
+    #[test]
+    fn repl_eval_cond_without_match() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(cond false 1 (= 1 2) 2)")));
+        assert_eq!(format!(""), repl.eval(String::from("(cond)")));
+    }
+
+    #[test]
+    fn repl_eval_when() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("199"), repl.eval(String::from("(when true (199))")));
+        assert_eq!(format!(""), repl.eval(String::from("(when false (0))")));
+    }
+
+    #[test]
+    fn repl_eval_when_with_body() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("3"),
+            repl.eval(String::from("(when true (def x 1) (def y 2) (+ x y))"))
+        );
+        assert_eq!(format!(""), repl.eval(String::from("(when false (def z 1) z)")));
+    }
+
+    #[test]
+    fn repl_eval_do() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("2"), repl.eval(String::from("(do 1 2)")));
+        assert_eq!(format!(""), repl.eval(String::from("(do)")));
+        assert_eq!(
+            format!("10"),
+            repl.eval(String::from("(if true (do (def a 5) (* a 2)) 0)"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod strings {
+    use super::*;
+
+    #[test]
+    fn repl_eval_str_escapes() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("a \"quoted\"\tword\n"),
+            repl.eval(String::from(r#""a \"quoted\"\tword\n""#))
+        );
+        assert_eq!(format!("caf\u{e9}"), repl.eval(String::from(r#""caf\u{e9}""#)));
+    }
+
+    #[test]
+    fn repl_eval_raw_str() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("\\d+\\s"),
+            repl.eval(String::from(r##"#"\d+\s""##))
+        );
+    }
+
+    #[test]
+    fn repl_eval_unterminated_str() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: unterminated string `\"` at line 2, column 8"),
+            repl.eval(String::from("(def x 1)\n(print \"x)"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod lists {
+    use super::*;
+
+    #[test]
+    fn repl_eval_list() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("(1 2 true)"), repl.eval(String::from("'(1  2 true)")));
+    }
+
+    #[test]
+    fn repl_eval_nested_list() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("(1 (2 3) (a b 4) (name false))"),
+            repl.eval(String::from("'(1 (2 3) (\"a b\" 4) (name false))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_list_variable() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def xs '(1 (2)))")));
+        assert_eq!(format!("(1 (2))"), repl.eval(String::from("xs")));
+    }
+}
+
+#[cfg(test)]
+mod indentifier {
+    use super::*;
+
+    #[test]
+    fn repl_eval_variables() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def x \"moeda\")")));
+        assert_eq!(
+            format!("eq"),
+            repl.eval(String::from("(if (/= x \"moeda\") (\"neq\") (\"eq\"))"))
+        )
+    }
+
+    #[test]
+    fn repl_eval_kebab_case_variables() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def max-size 10)")));
+        assert_eq!(format!(""), repl.eval(String::from("(def *debug* true)")));
+        assert_eq!(
+            format!("9"),
+            repl.eval(String::from("(if *debug* (- max-size 1) 0)"))
+        )
+    }
+
+    #[test]
+    fn repl_eval_predicate_functions() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn zero? [n] (= n 0))"));
+        repl.eval(String::from("(defn str->int [s] (if (= s \"one\") 1 0))"));
+        assert_eq!(format!("true"), repl.eval(String::from("(zero? 0)")));
+        assert_eq!(format!("1"), repl.eval(String::from("(str->int \"one\")")));
+    }
+
+    #[test]
+    fn repl_eval_variables_already_defined() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def x \"moeda\")")));
+        assert_eq!(
+            format!("Value error: variable x has already defined at line 1, column 6"),
+            repl.eval(String::from("(def x \"rust\")"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_variables_not_defined() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def y \"moeda\")")));
+        assert_eq!(
+            format!("Variable x doesn't exist in this context at line 1, column 8"),
+            repl.eval(String::from("(print x)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_variables_not_defined_multiline() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Variable x doesn't exist in this context at line 2, column 6"),
+            repl.eval(String::from("(+ 1\n  (* x 2))"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod functions {
+    use super::*;
+
+    #[test]
+    fn repl_eval_defn() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(defn f [] (true))")));
+        assert_eq!(format!("true"), repl.eval(String::from("(f)")));
+    }
+
+    #[test]
+    fn repl_eval_defn_with_args() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!(""),
+            repl.eval(String::from("(defn f [n t] (* n t))"))
+        );
+        assert_eq!(format!("8"), repl.eval(String::from("(f 2 4)")));
+    }
+
+    #[test]
+    fn repl_eval_defn_variadic() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn f [a & more] (list a more))"));
+        assert_eq!(format!("(1 (2 3))"), repl.eval(String::from("(f 1 2 3)")));
+        assert_eq!(format!("(1 ())"), repl.eval(String::from("(f 1)")));
+    }
+
+    #[test]
+    fn repl_eval_fn_variadic() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("3"), repl.eval(String::from("((fn [& xs] (count xs)) 1 2 3)")));
+    }
+
+    #[test]
+    fn repl_eval_variadic_params_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: expected a single name after `&` at line 1, column 12"),
+            repl.eval(String::from("(defn f [a &] a)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_apply() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn add [a b c] (+ a b c))"));
+        assert_eq!(format!("6"), repl.eval(String::from("(apply add (list 1 2 3))")));
+        assert_eq!(format!("6"), repl.eval(String::from("(apply add 1 (list 2 3))")));
+        assert_eq!(format!("(0 1)"), repl.eval(String::from("(apply cons 0 (list (list 1)))")));
+    }
+
+    #[test]
+    fn repl_eval_apply_variadic() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn sum [& xs] (if (empty? xs) 0 (+ (first xs) (apply sum (rest xs)))))"));
+        assert_eq!(format!("10"), repl.eval(String::from("(apply sum (range 5))")));
+    }
+
+    #[test]
+    fn repl_eval_operators_as_values() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("3"), repl.eval(String::from("(apply + (list 1 2))")));
+        assert_eq!(format!("9"), repl.eval(String::from("(apply max 3 (list 9 2))")));
+        assert_eq!(format!("true"), repl.eval(String::from("(apply = (list 1 1))")));
+        repl.eval(String::from("(defn twice [f x] (f (f x)))"));
+        assert_eq!(format!("3"), repl.eval(String::from("(twice inc 1)")));
+        assert_eq!(format!("#<fn not>"), repl.eval(String::from("not")));
+    }
+
+    #[test]
+    fn repl_eval_apply_not_a_list() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Value error: apply expects a list, got 2 at line 1, column 1"),
+            repl.eval(String::from("(apply list 1 2)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_defn_already_defined() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(defn f [] (true))")));
+        assert_eq!(
+            format!("Value error: variable f has already defined at line 1, column 7"),
+            repl.eval(String::from("(defn f [] (false))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_defn_call_function() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(defn f [n] (* n n))")));
+        assert_eq!(format!("4"), repl.eval(String::from("(f 2)")));
+    }
+
+    #[test]
+    fn repl_eval_defn_call_function_with_more_args() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!(""),
+            repl.eval(String::from("(defn f [n t] (+ (* n n) t))"))
+        );
+        assert_eq!(format!("9"), repl.eval(String::from("(f 2 5)")));
+    }
+
+    #[test]
+    fn repl_eval_defn_call_function_not_defined() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(defn f [n] (* n n))")));
+        assert_eq!(
+            format!("Value error: g is not callable at line 1, column 2"),
+            repl.eval(String::from("(g 2)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_fn_call() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("9"), repl.eval(String::from("((fn [x] (* x x)) 3)")));
+        assert_eq!(format!("#<fn>"), repl.eval(String::from("(fn [x] x)")));
+    }
+
+    #[test]
+    fn repl_eval_fn_as_argument() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn twice [f x] (f (f x)))"));
+        repl.eval(String::from("(defn inc-one [x] (+ x 1))"));
+        assert_eq!(format!("12"), repl.eval(String::from("(twice (fn [x] (* x 2)) 3)")));
+        assert_eq!(format!("5"), repl.eval(String::from("(twice inc-one 3)")));
+    }
+
+    #[test]
+    fn repl_eval_fn_returned() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn make-square [] (fn [x] (* x x)))"));
+        assert_eq!(format!("16"), repl.eval(String::from("((make-square) 4)")));
+    }
+
+    #[test]
+    fn repl_eval_fn_in_list() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(def fs (list (fn [x] (+ x 1)) (fn [x] (* x 2))))"));
+        assert_eq!(format!("6"), repl.eval(String::from("((first fs) 5)")));
+        assert_eq!(format!("10"), repl.eval(String::from("((first (rest fs)) 5)")));
+    }
+
+    #[test]
+    fn repl_eval_closure() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn make-adder [n] (fn [x] (+ x n)))"));
+        assert_eq!(format!("3"), repl.eval(String::from("((make-adder 1) 2)")));
+    }
+
+    #[test]
+    fn repl_eval_closure_keeps_its_bindings() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn make-adder [n] (fn [x] (+ x n)))"));
+        repl.eval(String::from("(def add-ten (make-adder 10))"));
+        repl.eval(String::from("(def add-one (make-adder 1))"));
+        repl.eval(String::from("(defn call-with [n f] (f n))"));
+        assert_eq!(format!("15"), repl.eval(String::from("(add-ten 5)")));
+        assert_eq!(format!("51"), repl.eval(String::from("(call-with 50 add-one)")));
+    }
+
+    #[test]
+    fn repl_eval_local_recursive_function() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn outer [n] (defn down [i] (if (= i 0) 0 (down (dec i)))) (down n))",
+        ));
+        assert_eq!(format!("0"), repl.eval(String::from("(outer 3)")));
+    }
+
+    #[test]
+    fn repl_eval_local_mutually_recursive_functions() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn outer [] (defn a [n] (if (= n 0) 0 (b (dec n)))) (defn b [n] (a n)) (a 3))",
+        ));
+        assert_eq!(format!("0"), repl.eval(String::from("(outer)")));
+    }
+
+    #[test]
+    fn repl_eval_closure_sees_its_scope_at_run_time() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn f [y] (fn [] (eval 'y)))"));
+        assert_eq!(format!("4"), repl.eval(String::from("((f 4))")));
+    }
+
+    #[test]
+    fn repl_eval_lexical_scope() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn get-y [] y)"));
+        repl.eval(String::from("(defn outer [y] (get-y))"));
+        assert_eq!(
+            format!("Variable y doesn't exist in this context at line 1, column 16"),
+            repl.eval(String::from("(outer 5)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_call_not_a_function() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Value error: 3 is not callable at line 1, column 2"),
+            repl.eval(String::from("((+ 1 2) 4)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_too_few_arguments() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn hello [name] name)"));
+        assert_eq!(
+            format!("Value error: hello expects 1 argument, got 0 at line 1, column 1"),
+            repl.eval(String::from("(hello)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_too_many_arguments() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn add [a b] (+ a b))"));
+        assert_eq!(
+            format!("Value error: add expects 2 arguments, got 3 at line 1, column 1"),
+            repl.eval(String::from("(add 1 2 3)"))
+        );
+        assert_eq!(
+            format!("Value error: fn expects 0 arguments, got 1 at line 1, column 1"),
+            repl.eval(String::from("((fn [] 1) 2)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_variadic_too_few_arguments() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn f [a & more] a)"));
+        assert_eq!(
+            format!("Value error: f expects at least 1 argument, got 0 at line 1, column 1"),
+            repl.eval(String::from("(f)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_arity_error_in_tail_call() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn one [a] a)"));
+        repl.eval(String::from("(defn f [] (one))"));
+        assert_eq!(
+            format!("Value error: one expects 1 argument, got 0 at line 1, column 12"),
+            repl.eval(String::from("(f)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_multi_arity_defn() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn greet ([] (greet \"world\")) ([name] name) ([a b & more] (count more)))"));
+        assert_eq!(format!("world"), repl.eval(String::from("(greet)")));
+        assert_eq!(format!("moeda"), repl.eval(String::from("(greet \"moeda\")")));
+        assert_eq!(format!("1"), repl.eval(String::from("(greet 1 2 3)")));
+    }
+
+    #[test]
+    fn repl_eval_multi_arity_fn() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(def f (fn ([x] x) ([x y] (+ x y))))"));
+        assert_eq!(format!("3"), repl.eval(String::from("(f 1 2)")));
+        assert_eq!(
+            format!("Value error: fn expects 1 or 2 arguments, got 0 at line 1, column 1"),
+            repl.eval(String::from("(f)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_multi_arity_duplicate_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: another clause already takes 1 argument `(` at line 1, column 17"),
+            repl.eval(String::from("(defn f ([a] a) ([b] b))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_multi_arity_exact_before_variadic() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn f ([& xs] 0) ([a] 1))"));
+        assert_eq!(format!("1"), repl.eval(String::from("(f 9)")));
+        assert_eq!(format!("0"), repl.eval(String::from("(f 9 9)")));
+        repl.eval(String::from("(defn g ([a] 1) ([a & r] 2))"));
+        assert_eq!(format!("1"), repl.eval(String::from("(g 9)")));
+        assert_eq!(format!("2"), repl.eval(String::from("(g 9 9)")));
+    }
+
+    #[test]
+    fn repl_eval_multi_arity_two_variadic_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: another clause already has a rest param `(` at line 1, column 21"),
+            repl.eval(String::from("(defn f ([& xs] xs) ([a & r] r))"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod local_bindings {
+    use super::*;
+
+    #[test]
+    fn repl_eval_let() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("3"),
+            repl.eval(String::from("(let [a 1 b (+ a 1)] (+ a b))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_let_drops_bindings() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("1"), repl.eval(String::from("(let [a 1] (def b 2) a)")));
+        assert_eq!(
+            format!("Variable a doesn't exist in this context at line 1, column 1"),
+            repl.eval(String::from("a"))
+        );
+        assert_eq!(format!(""), repl.eval(String::from("(def b 3)")));
+        assert_eq!(format!("2"), repl.eval(String::from("(let [b 2] b)")));
+        assert_eq!(format!("3"), repl.eval(String::from("b")));
+    }
+
+    #[test]
+    fn repl_eval_let_in_function() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn hypot-sq [a b] (let [a2 (* a a) b2 (* b b)] (+ a2 b2)))",
+        ));
+        assert_eq!(format!("25"), repl.eval(String::from("(hypot-sq 3 4)")));
+        assert_eq!(format!("2"), repl.eval(String::from("(hypot-sq 1 1)")));
+    }
+
+    #[test]
+    fn repl_eval_let_closure() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(def add-two (let [n 2] (fn [x] (+ x n))))"));
+        assert_eq!(format!("7"), repl.eval(String::from("(add-two 5)")));
+    }
+}
+
+#[cfg(test)]
+mod iteration {
+    use super::*;
+
+    #[test]
+    fn repl_eval_loop() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("55"),
+            repl.eval(String::from(
+                "(loop [i 0 acc 0] (if (> i 10) acc (recur (inc i) (+ acc i))))"
+            ))
+        );
+    }
+
+    #[test]
+    fn repl_eval_loop_in_function() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn fact [n] (loop [i n acc 1] (if (< i 2) acc (recur (dec i) (* acc i)))))",
+        ));
+        assert_eq!(format!("120"), repl.eval(String::from("(fact 5)")));
+    }
+
+    #[test]
+    fn repl_eval_loop_many_iterations() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("5000050000"),
+            repl.eval(String::from(
+                "(loop [i 0 acc 0] (cond (> i 100000) acc :else (recur (inc i) (+ acc i))))"
+            ))
+        );
+    }
+
+    #[test]
+    fn repl_eval_loop_drops_bindings() {
+        let mut repl = Repl::new();
+        assert_eq!(format!("0"), repl.eval(String::from("(loop [i 0] i)")));
+        assert_eq!(
+            format!("Variable i doesn't exist in this context at line 1, column 1"),
+            repl.eval(String::from("i"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_loop_iteration_frames() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(def fs (loop [i 0 acc (list)] (if (< i 3) (recur (inc i) (cons (fn [] i) acc)) acc)))",
+        ));
+        assert_eq!(format!("0"), repl.eval(String::from("((first (rest (rest fs))))")));
+        assert_eq!(format!("2"), repl.eval(String::from("((first fs))")));
+        assert_eq!(
+            format!("2"),
+            repl.eval(String::from("(loop [i 0] (def x i) (if (< i 2) (recur (inc i)) x))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_recur_arity_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: recur expects 1 argument, got 0 `recur` at line 1, column 13"),
+            repl.eval(String::from("(loop [i 0] (recur))"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_recur_not_in_tail_position() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: recur must be in tail position `recur` at line 1, column 22"),
+            repl.eval(String::from("(loop [i 0] (+ 1 (do (recur i) 1)))"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tail_calls {
+    use super::*;
+
+    #[test]
+    fn repl_eval_tail_recursion_over_long_list() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn sum-list [xs acc] (if (empty? xs) acc (sum-list (rest xs) (+ acc (first xs)))))",
+        ));
+        assert_eq!(
+            format!("499999500000"),
+            repl.eval(String::from("(sum-list (range 1000000) 0)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_mutual_recursion() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn my-even? [n] (if (= n 0) true (my-odd? (dec n))))"));
+        repl.eval(String::from("(defn my-odd? [n] (if (= n 0) false (my-even? (dec n))))"));
+        assert_eq!(format!("true"), repl.eval(String::from("(my-even? 100000)")));
+        assert_eq!(format!("true"), repl.eval(String::from("(my-odd? 100001)")));
+    }
+
+    #[test]
+    fn repl_eval_tail_call_in_nested_forms() {
+        let mut repl = Repl::new();
+        repl.eval(String::from(
+            "(defn count-down [n] (cond (= n 0) \"done\" :else (let [m (dec n)] (do (count-down m)))))",
+        ));
+        assert_eq!(format!("done"), repl.eval(String::from("(count-down 100000)")));
+    }
+
+    #[test]
+    fn repl_eval_non_tail_call_keeps_its_frame() {
+        let mut repl = Repl::new();
+        repl.eval(String::from("(defn len [xs] (if (empty? xs) 0 (+ 1 (len (rest xs)))))"));
+        assert_eq!(format!("3"), repl.eval(String::from("(len (list 1 2 3))")));
+    }
+}
+
+#[cfg(test)]
+mod syntax_error {
+    use super::*;
+
+    #[test]
+    fn repl_eval_syntax_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: expected ID found Integer `1` at line 1, column 6"),
+            repl.eval(String::from("(def 1 2)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_keeps_session_after_syntax_error() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(def x 2)")));
+        assert_eq!(
+            format!("Syntax error: unclosed `(` at line 1, column 1"),
+            repl.eval(String::from("(+ x 1"))
+        );
+        assert_eq!(format!("3"), repl.eval(String::from("(+ x 1)")));
+    }
+
+    #[test]
+    fn repl_eval_unclosed_form_stops_at_next_line() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Syntax error: unclosed `(` at line 1, column 1"),
+            repl.eval(String::from("(def x (+ 1 2)\n(def y 3)\n(print y)"))
+        );
+    }
+
+    #[test]
+    fn repl_eval_reports_every_syntax_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!(
+                "Syntax error: unknown form head `1` at line 1, column 5\n\
+                 Syntax error: expected ArgsBegin found ID `x` at line 2, column 9"
+            ),
+            repl.eval(String::from("(+ (1 2) 3)\n(defn f x (* x 2))"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod stdout {
+    use super::*;
+
+    #[test]
+    fn repl_eval_print() {
+        let mut repl = Repl::new();
+        assert_eq!(format!(""), repl.eval(String::from("(print true)")));
+    }
+}
+
+
+#[cfg(test)]
+mod programs {
+    use super::*;
+
+    #[test]
+    fn repl_multiple4_program() {
+        let mut repl = Repl::new();
+        let mut source_code = r#"(defn mult4 [x] (* x 4))"#;
+        repl.eval(String::from(source_code));
+        source_code = "(defn resultado [y] (mult4 y))";
+        repl.eval(String::from(source_code));
+        source_code = "(resultado 5)";
+        assert_eq!(format!("20"), repl.eval(String::from(source_code)));
+    }
+
+    #[test]
+    fn repl_multiple_forms_program() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("2"),
+            repl.eval(String::from("(def x 1) (def y (+ x 1)) y"))
+        );
+    }
+
+    #[test]
+    fn repl_multiline_program() {
+        let mut repl = Repl::new();
+        let source_code = "
+            ;; squares the argument
+            (defn square [n] (* n n))
+
+            (def side 4)
+            (square side)";
+        assert_eq!(format!("16"), repl.eval(String::from(source_code)));
+    }
+
+    #[test]
+    fn repl_program_stops_at_first_error() {
+        let mut repl = Repl::new();
+        assert_eq!(
+            format!("Variable y doesn't exist in this context at line 1, column 14"),
+            repl.eval(String::from("(def x 1) (+ y 1) (def z 2)"))
+        );
+        assert_eq!(format!("1"), repl.eval(String::from("x")));
+        assert_eq!(
+            format!("Variable z doesn't exist in this context at line 1, column 1"),
+            repl.eval(String::from("z"))
+        );
+    }
+
+    #[test]
+    fn repl_fibonacci_program() {
+        let mut repl = Repl::new();
+        let mut source_code = "(defn fib [n] (if (or (= n 1) (= n 2)) 1 (+ (fib (- n 1)) (fib (- n 2)))))";
+        assert_eq!(format!(""), repl.eval(String::from(source_code)));
+        source_code = "(fib 7)";
+        assert_eq!(format!("13"), repl.eval(String::from(source_code)));
+    }
+}
+
+#[cfg(test)]
+mod script {
+    use super::*;
+
+    fn run_script(name: &str, source_code: &str) -> (bool, String, String) {
+        let path = env::temp_dir().join(name);
+        fs::write(&path, source_code).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_moeda"))
+            .arg(&path)
+            .output()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        (
+            output.status.success(),
+            String::from_utf8(output.stdout).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    }
+
+    #[test]
+    fn repl_run_returns_error() {
+        let mut repl = Repl::new();
+        assert_eq!(Ok(String::from("2")), repl.run(String::from("(+ 1 1)")));
+        assert_eq!(
+            Err(String::from(
+                "Variable x doesn't exist in this context at line 1, column 1"
+            )),
+            repl.run(String::from("x"))
+        );
+    }
+
+    #[test]
+    fn script_runs_every_form() {
+        let source_code = "#!/usr/bin/env moeda\n\
+                           (defn square [n] (* n n))\n\
+                           (print (square 5))\n";
+        let (success, stdout, stderr) = run_script("moeda_script_ok.mda", source_code);
+        assert!(success);
+        assert_eq!("25", stdout);
+        assert_eq!("", stderr);
+    }
+
+    #[test]
+    fn script_fails_with_error() {
+        let source_code = "(print 1)\n(+ x 1)\n";
+        let (success, stdout, stderr) = run_script("moeda_script_error.mda", source_code);
+        assert!(!success);
+        assert_eq!("1", stdout);
+        assert!(
+            stderr.ends_with("moeda_script_error.mda: Variable x doesn't exist in this context at line 2, column 4\n")
+        );
     }
 }